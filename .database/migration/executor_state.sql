@@ -0,0 +1,9 @@
+DROP TABLE IF EXISTS `executor_state`;
+CREATE TABLE IF NOT EXISTS `executor_state` (
+    `key` VARCHAR(255) NOT NULL,
+    -- the value only counts within this period, e.g. a day
+    `period` VARCHAR(64) NOT NULL,
+    `value` BIGINT NOT NULL,
+    `updated_at` TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY(`key`)
+);
@@ -0,0 +1,7 @@
+DROP TABLE IF EXISTS `scheduler_member`;
+CREATE TABLE IF NOT EXISTS `scheduler_member` (
+    `instance_id` VARCHAR(64) NOT NULL,
+    `heartbeat_at` TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    `created_at` TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY(`instance_id`)
+);
@@ -14,6 +14,10 @@ CREATE TABLE IF NOT EXISTS `task` (
     `execute_times` INT NULL,
     `last_executed_at` TIMESTAMP,
     `event_id` INT NULL,
+    -- content template for generic template executors, overrides theirs
+    `template` TEXT NULL,
+    `last_status` VARCHAR(32) NULL,
+    `last_error` TEXT NULL,
     `created_at` TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
     `updated_at` TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP NOT NULL,
     `deleted_at` TIMESTAMP,
@@ -0,0 +1,10 @@
+DROP TABLE IF EXISTS `task_change`;
+CREATE TABLE IF NOT EXISTS `task_change` (
+    `id` BIGINT AUTO_INCREMENT,
+    `task_id` BIGINT NOT NULL,
+    -- created, updated or deleted
+    `kind` VARCHAR(16) NOT NULL,
+    `created_at` TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY(`id`),
+    KEY `idx_created_at` (`created_at`)
+);
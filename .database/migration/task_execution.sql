@@ -0,0 +1,24 @@
+DROP TABLE IF EXISTS `task_execution`;
+CREATE TABLE IF NOT EXISTS `task_execution` (
+    `id` BIGINT AUTO_INCREMENT,
+    `task_id` BIGINT NOT NULL,
+    `run_id` BIGINT NULL,
+    `occurrence_key` VARCHAR(128) NOT NULL,
+    `sequence` INT NOT NULL,
+    `scheduled_at` TIMESTAMP NOT NULL,
+    `started_at` TIMESTAMP(3) NOT NULL,
+    `finished_at` TIMESTAMP(3) NOT NULL,
+    `worker_id` VARCHAR(64) NULL,
+    `event_id` INT NULL,
+    `executor` VARCHAR(64) NULL,
+    `sender` VARCHAR(64) NULL,
+    `content` TEXT NULL,
+    `response_code` BIGINT NULL,
+    `response_message` VARCHAR(255) NULL,
+    `succeeded` BOOLEAN NOT NULL,
+    `error` TEXT NULL,
+    `created_at` TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY(`id`),
+    KEY `idx_task_id_scheduled_at` (`task_id`, `scheduled_at`),
+    KEY `idx_occurrence_key` (`occurrence_key`)
+);
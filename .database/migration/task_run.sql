@@ -0,0 +1,24 @@
+DROP TABLE IF EXISTS `task_run`;
+CREATE TABLE IF NOT EXISTS `task_run` (
+    `id` BIGINT AUTO_INCREMENT,
+    `task_id` BIGINT NOT NULL,
+    `event_id` INT NULL,
+    `occurrence_key` VARCHAR(128) NOT NULL,
+    `sequence` INT NOT NULL,
+    `scheduled_at` TIMESTAMP NOT NULL,
+    `dispatched_at` TIMESTAMP NOT NULL,
+    -- pending, running, succeeded, failed, dead
+    `state` VARCHAR(16) NOT NULL,
+    `attempts` INT DEFAULT 0 NOT NULL,
+    -- failed runs are not claimed again before their backoff elapsed
+    `available_at` TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    `worker_id` VARCHAR(64) NULL,
+    `lease_expires_at` TIMESTAMP NULL,
+    `heartbeat_at` TIMESTAMP NULL,
+    `last_error` TEXT NULL,
+    `created_at` TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    `updated_at` TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY(`id`),
+    UNIQUE KEY `uk_occurrence_key` (`occurrence_key`),
+    KEY `idx_state_available_at` (`state`, `available_at`)
+);
@@ -1,16 +1,40 @@
-use std::env;
+use std::{env, sync::Arc, time::Duration};
 
 extern crate executor;
 
-use executor::{new_executor_manager, WATERBOT_ID};
+use executor::{new_executor_manager, serve, shutdown_signal, Config};
+use tokio::{net::TcpListener, sync::watch, time};
 
-fn main() -> anyhow::Result<()> {
+static DEFAULT_ADDR: &str = "0.0.0.0:8080";
+// how long in-flight executions may take to finish after a shutdown signal
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
     dotenv::dotenv().ok();
-    let dingtalk_url = env::var("DINGTALK_URL")?;
-    println!("DingTalk Url: {}", dingtalk_url);
-    let executor = new_executor_manager(&dingtalk_url);
-    for _ in 0..1 {
-        executor.execute(WATERBOT_ID)?;
+    let addr = env::var("EXECUTOR_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.into());
+    let manager = match env::var("EXECUTOR_CONFIG") {
+        Ok(path) => Config::load_manager(&path).await?,
+        Err(_) => {
+            let dingtalk_url = env::var("DINGTALK_URL")?;
+            println!("DingTalk Url: {}", dingtalk_url);
+            Arc::new(new_executor_manager(&dingtalk_url))
+        }
+    };
+
+    let listener = TcpListener::bind(&addr).await?;
+    println!("Executor service listening on {}", listener.local_addr()?);
+    let signal = shutdown_signal()?;
+    let (stopping, mut stopped) = watch::channel(false);
+    let server = serve(listener, manager, async move {
+        signal.await;
+        stopping.send_replace(true);
+    });
+    tokio::select! {
+        served = server => served,
+        _ = async {
+            let _ = stopped.wait_for(|stopped| *stopped).await;
+            time::sleep(DRAIN_TIMEOUT).await;
+        } => anyhow::bail!("in-flight executions did not finish in {:?}", DRAIN_TIMEOUT),
     }
-    Ok(())
 }
@@ -1,8 +1,21 @@
-mod waterbot;
-mod manager;
+mod context;
+mod counter;
+pub(crate) mod manager;
+mod outcome;
+mod registration;
+pub(crate) mod template;
+pub(crate) mod waterbot;
 
-pub use manager::{new_executor_manager, WATERBOT_ID};
+use async_trait::async_trait;
 
-trait Executor {
-    fn execute(&self) -> anyhow::Result<()>;
+pub use context::{Clock, ExecutionContext, SystemClock};
+pub use counter::{Period, PeriodicCounter};
+pub use manager::{new_executor_manager, ExecutorManager, WATERBOT_ID};
+pub use outcome::ExecutionOutcome;
+pub use registration::{ExecutorId, ExecutorMetadata, ExecutorNotFound};
+
+/// Something to do when a task is due, e.g. sending a reminder.
+#[async_trait]
+pub trait Executor: Send + Sync {
+    async fn execute(&self, ctx: &ExecutionContext) -> anyhow::Result<ExecutionOutcome>;
 }
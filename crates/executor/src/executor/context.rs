@@ -0,0 +1,124 @@
+use std::{fmt, sync::Arc};
+
+use chrono::{DateTime, Local};
+use tokio_util::sync::CancellationToken;
+
+use crate::{ExecutorId, MemoryStateStore, StateStore};
+
+/// Source of the current time, replaced in tests to pin executions to a
+/// given moment.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Local>;
+}
+
+/// The wall clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// What an executor knows about the occurrence it executes.
+#[derive(Clone)]
+pub struct ExecutionContext {
+    task_id: i32,
+    scheduled_at: DateTime<Local>,
+    attempt: u32,
+    payload: serde_json::Value,
+    clock: Arc<dyn Clock>,
+    cancellation: CancellationToken,
+    executor_id: Option<ExecutorId>,
+    state: Arc<dyn StateStore>,
+}
+
+impl ExecutionContext {
+    pub fn new(task_id: i32, scheduled_at: DateTime<Local>) -> ExecutionContext {
+        ExecutionContext {
+            task_id,
+            scheduled_at,
+            attempt: 1,
+            payload: serde_json::Value::Null,
+            clock: Arc::new(SystemClock),
+            cancellation: CancellationToken::new(),
+            executor_id: None,
+            state: Arc::new(MemoryStateStore::new()),
+        }
+    }
+
+    /// 1-based attempt of the run, 1 by default.
+    pub fn with_attempt(mut self, attempt: u32) -> Self {
+        self.attempt = attempt.max(1);
+        self
+    }
+
+    pub fn with_payload(mut self, payload: serde_json::Value) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Cancelled when the caller gives up on the execution, e.g. on shutdown.
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Where executors keep values across executions, set by the
+    /// `ExecutorManager` to its own store.
+    pub fn with_state_store(mut self, state: Arc<dyn StateStore>) -> Self {
+        self.state = state;
+        self
+    }
+
+    pub(crate) fn with_executor_id(mut self, id: ExecutorId) -> Self {
+        self.executor_id = Some(id);
+        self
+    }
+
+    pub fn task_id(&self) -> i32 {
+        self.task_id
+    }
+    /// When the occurrence was due, not when it is executed.
+    pub fn scheduled_at(&self) -> DateTime<Local> {
+        self.scheduled_at
+    }
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+    pub fn payload(&self) -> &serde_json::Value {
+        &self.payload
+    }
+    pub fn now(&self) -> DateTime<Local> {
+        self.clock.now()
+    }
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+    /// Id the running executor is registered under.
+    pub fn executor_id(&self) -> Option<&ExecutorId> {
+        self.executor_id.as_ref()
+    }
+    pub fn state_store(&self) -> &dyn StateStore {
+        self.state.as_ref()
+    }
+}
+
+impl fmt::Debug for ExecutionContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExecutionContext")
+            .field("task_id", &self.task_id)
+            .field("scheduled_at", &self.scheduled_at)
+            .field("attempt", &self.attempt)
+            .field("executor_id", &self.executor_id)
+            .field("payload", &self.payload)
+            .field("cancelled", &self.cancellation.is_cancelled())
+            .finish()
+    }
+}
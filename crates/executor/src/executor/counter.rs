@@ -0,0 +1,226 @@
+use std::{fmt, future::Future, str::FromStr};
+
+use chrono::{DateTime, Local, NaiveTime, TimeDelta, Timelike};
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+use super::context::ExecutionContext;
+
+/// Calendar period a `PeriodicCounter` counts in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    #[default]
+    Daily,
+    /// ISO weeks, starting on Monday.
+    Weekly,
+    Monthly,
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Period::Daily => write!(f, "daily"),
+            Period::Weekly => write!(f, "weekly"),
+            Period::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+/// Counter starting over whenever a new period begins, e.g. the "第N轮" of the
+/// WaterBot counting the rounds of the day. Periods begin at the reset time of
+/// day in the counter's time zone, so the count follows the calendar no matter
+/// when the executions happen. The count lives in the state store of the
+/// context, restarts and other instances continue it.
+#[derive(Debug, Clone)]
+pub struct PeriodicCounter {
+    name: String,
+    period: Period,
+    reset_at: NaiveTime,
+    time_zone: Option<Tz>,
+}
+
+impl PeriodicCounter {
+    /// A daily counter resetting at midnight, local time.
+    pub fn new(name: &str) -> PeriodicCounter {
+        PeriodicCounter {
+            name: name.into(),
+            period: Period::Daily,
+            reset_at: NaiveTime::MIN,
+            time_zone: None,
+        }
+    }
+
+    pub fn with_period(mut self, period: Period) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Time of day the periods begin at, midnight by default. A daily counter
+    /// resetting at 04:00 counts 01:00 into the day before.
+    pub fn with_reset_at(mut self, reset_at: NaiveTime) -> Self {
+        self.reset_at = reset_at;
+        self
+    }
+
+    /// Time zone of the reset time, the local one of the process by default.
+    pub fn with_time_zone(mut self, time_zone: Tz) -> Self {
+        self.time_zone = Some(time_zone);
+        self
+    }
+
+    /// Parse an IANA time zone name such as `Asia/Shanghai`.
+    pub fn parse_time_zone(name: &str) -> anyhow::Result<Tz> {
+        Tz::from_str(name).map_err(|_| anyhow::anyhow!("unknown time zone {:?}", name))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Identifies the period `now` falls in, e.g. `2024-09-02`, `2024-W36` or
+    /// `2024-09`.
+    pub fn period_of(&self, now: &DateTime<Local>) -> String {
+        let local = match self.time_zone {
+            Some(time_zone) => now.with_timezone(&time_zone).naive_local(),
+            None => now.naive_local(),
+        };
+        let shifted = local - TimeDelta::seconds(self.reset_at.num_seconds_from_midnight().into());
+        let format = match self.period {
+            Period::Daily => "%Y-%m-%d",
+            Period::Weekly => "%G-W%V",
+            Period::Monthly => "%Y-%m",
+        };
+        shifted.format(format).to_string()
+    }
+
+    fn key(&self, ctx: &ExecutionContext) -> String {
+        match ctx.executor_id() {
+            Some(id) => format!("executor:{}:{}", id, self.name),
+            None => format!("executor:{}", self.name),
+        }
+    }
+
+    /// Count so far in the current period.
+    pub async fn current(&self, ctx: &ExecutionContext) -> anyhow::Result<i64> {
+        let period = self.period_of(&ctx.now());
+        let current = ctx.state_store().get(&self.key(ctx), &period).await?;
+        Ok(current.unwrap_or_default())
+    }
+
+    /// Run `send` with the next count, the count is handed back when it fails
+    /// so the next attempt sends it again.
+    pub async fn count<F, Fut, T>(&self, ctx: &ExecutionContext, send: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(i64) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let key = self.key(ctx);
+        let period = self.period_of(&ctx.now());
+        let count = ctx.state_store().incr(&key, &period, 1).await?;
+        let sent = send(count).await;
+        if sent.is_err() {
+            // a later count taken meanwhile keeps its number
+            let released = ctx
+                .state_store()
+                .compare_and_set(&key, &period, Some(count), count - 1)
+                .await;
+            if let Err(err) = released {
+                eprintln!("Failed to release {} {}: {:#}", self.name, count, err);
+            }
+        }
+        sent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::{FixedOffset, TimeZone};
+
+    use crate::{Clock, MemoryStateStore};
+
+    use super::*;
+
+    struct ManualClock(Mutex<DateTime<Local>>);
+
+    impl Clock for ManualClock {
+        fn now(&self) -> DateTime<Local> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn at(rfc3339: &str) -> DateTime<Local> {
+        DateTime::parse_from_rfc3339(rfc3339)
+            .unwrap()
+            .with_timezone(&Local)
+    }
+
+    #[test]
+    fn test_period_of() {
+        let shanghai = PeriodicCounter::parse_time_zone("Asia/Shanghai").unwrap();
+        let counter = PeriodicCounter::new("round")
+            .with_reset_at(NaiveTime::from_hms_opt(4, 0, 0).unwrap())
+            .with_time_zone(shanghai);
+        // 01:30 in Shanghai still belongs to the day before
+        assert_eq!(
+            "2024-09-01",
+            counter.period_of(&at("2024-09-02T01:30:00+08:00"))
+        );
+        assert_eq!(
+            "2024-09-02",
+            counter.period_of(&at("2024-09-02T04:00:00+08:00"))
+        );
+        assert_eq!("2024-09-02", counter.period_of(&at("2024-09-02T19:00:00Z")));
+
+        let counter = counter.with_period(Period::Weekly);
+        assert_eq!(
+            "2024-W36",
+            counter.period_of(&at("2024-09-02T04:00:00+08:00"))
+        );
+        assert_eq!(
+            "2024-W35",
+            counter.period_of(&at("2024-09-02T03:59:00+08:00"))
+        );
+        let counter = counter.with_period(Period::Monthly);
+        assert_eq!(
+            "2024-08",
+            counter.period_of(&at("2024-09-01T03:00:00+08:00"))
+        );
+
+        assert!(PeriodicCounter::parse_time_zone("Mars/Olympus").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_count() -> anyhow::Result<()> {
+        let utc8 = FixedOffset::east_opt(8 * 3600).unwrap();
+        let clock = Arc::new(ManualClock(Mutex::new(at("2024-09-02T17:00:00+08:00"))));
+        let ctx = ExecutionContext::new(0, Local::now())
+            .with_clock(clock.clone())
+            .with_state_store(Arc::new(MemoryStateStore::new()));
+        let counter = PeriodicCounter::new("round")
+            .with_time_zone(PeriodicCounter::parse_time_zone("Asia/Shanghai")?);
+        let sent = |round| async move { Ok(round) };
+
+        assert_eq!(1, counter.count(&ctx, sent).await?);
+        let failed = counter
+            .count(&ctx, |_| async {
+                Err::<i64, _>(anyhow::anyhow!("send failed"))
+            })
+            .await;
+        assert!(failed.is_err());
+        // the failed round is sent again
+        assert_eq!(2, counter.count(&ctx, sent).await?);
+        // the evening keeps counting, it used to start over after 18:00
+        *clock.0.lock().unwrap() = utc8.with_ymd_and_hms(2024, 9, 2, 19, 0, 0).unwrap().into();
+        assert_eq!(3, counter.count(&ctx, sent).await?);
+        assert_eq!(4, counter.count(&ctx, sent).await?);
+        assert_eq!(4, counter.current(&ctx).await?);
+
+        *clock.0.lock().unwrap() = utc8.with_ymd_and_hms(2024, 9, 3, 9, 0, 0).unwrap().into();
+        assert_eq!(0, counter.current(&ctx).await?);
+        assert_eq!(1, counter.count(&ctx, sent).await?);
+        Ok(())
+    }
+}
@@ -1,39 +1,240 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
 
-use crate::sender::dingtalk::DingTalkSender;
+use crate::{DingTalkSender, MemoryStateStore, RetryPolicy, StateStore};
 
-use super::{waterbot::WaterBot, Executor};
+use super::{
+    context::ExecutionContext,
+    outcome::ExecutionOutcome,
+    registration::{ExecutorId, ExecutorMetadata, ExecutorNotFound},
+    waterbot::WaterBot,
+    Executor,
+};
 
 pub static WATERBOT_ID: usize = 0;
-static WATERBOT_RESET_HOUR: u32 = 18;
 
+struct Registration {
+    executor: Arc<dyn Executor>,
+    metadata: ExecutorMetadata,
+}
+
+/// Registry of the executors tasks can be bound to. Executors may be
+/// registered, replaced and removed while it is shared.
 pub struct ExecutorManager {
-    executors: HashMap<usize, Box<dyn Executor>>,
+    executors: RwLock<BTreeMap<ExecutorId, Registration>>,
+    retry: RetryPolicy,
+    state: Arc<dyn StateStore>,
+}
+
+impl Default for ExecutorManager {
+    fn default() -> Self {
+        ExecutorManager::new()
+    }
 }
 
 impl ExecutorManager {
-    fn new() -> ExecutorManager {
+    pub fn new() -> ExecutorManager {
         ExecutorManager {
-            executors: HashMap::new(),
+            executors: RwLock::new(BTreeMap::new()),
+            retry: RetryPolicy::none(),
+            state: Arc::new(MemoryStateStore::new()),
         }
     }
 
-    fn set_executor<T: Executor + 'static>(&mut self, id: usize, executor: T) {
-        self.executors.insert(id, Box::new(executor));
+    /// Retry policy of a whole execution on top of the sender's own retries,
+    /// a single attempt by default.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Where the executors keep their state, in memory by default. Share a
+    /// persistent store to keep counters across restarts and instances.
+    pub fn with_state_store(mut self, state: Arc<dyn StateStore>) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Register the executor under `id`. Returns the metadata of the executor
+    /// it replaced, if any.
+    pub fn register<E: Executor + 'static>(
+        &self,
+        id: impl Into<ExecutorId>,
+        executor: E,
+        metadata: ExecutorMetadata,
+    ) -> Option<ExecutorMetadata> {
+        let registration = Registration {
+            executor: Arc::new(executor),
+            metadata,
+        };
+        self.executors
+            .write()
+            .unwrap()
+            .insert(id.into(), registration)
+            .map(|previous| previous.metadata)
+    }
+
+    /// Unregister the executor, executions already started finish normally.
+    pub fn remove(&self, id: impl Into<ExecutorId>) -> Option<ExecutorMetadata> {
+        self.executors
+            .write()
+            .unwrap()
+            .remove(&id.into())
+            .map(|registration| registration.metadata)
+    }
+
+    pub fn contains(&self, id: impl Into<ExecutorId>) -> bool {
+        self.executors.read().unwrap().contains_key(&id.into())
+    }
+
+    pub fn metadata(&self, id: impl Into<ExecutorId>) -> Option<ExecutorMetadata> {
+        let executors = self.executors.read().unwrap();
+        executors
+            .get(&id.into())
+            .map(|registration| registration.metadata.clone())
     }
 
-    pub fn execute(&self, id: usize) -> anyhow::Result<()> {
-        if let Some(executor) = self.executors.get(&id) {
-            executor.execute()?;
+    /// Registered executors ordered by id.
+    pub fn list(&self) -> Vec<(ExecutorId, ExecutorMetadata)> {
+        let executors = self.executors.read().unwrap();
+        executors
+            .iter()
+            .map(|(id, registration)| (id.clone(), registration.metadata.clone()))
+            .collect()
+    }
+
+    /// Run the executor until it finishes or the context is cancelled. Fails
+    /// with `ExecutorNotFound` when nothing is registered under `id`.
+    pub async fn execute(
+        &self,
+        id: impl Into<ExecutorId>,
+        ctx: &ExecutionContext,
+    ) -> anyhow::Result<ExecutionOutcome> {
+        let id = id.into();
+        let executor = {
+            let executors = self.executors.read().unwrap();
+            match executors.get(&id) {
+                Some(registration) => registration.executor.clone(),
+                None => return Err(ExecutorNotFound(id).into()),
+            }
+        };
+        let ctx = ctx
+            .clone()
+            .with_executor_id(id.clone())
+            .with_state_store(self.state.clone());
+        let ctx = &ctx;
+        tokio::select! {
+            outcome = self.retry.run(|_| executor.execute(ctx)) => outcome,
+            _ = ctx.cancellation().cancelled() => {
+                anyhow::bail!("execution of executor {} cancelled", id)
+            }
         }
-        Ok(())
     }
 }
 
 pub fn new_executor_manager(dingtalk_url: &str) -> ExecutorManager {
     let sender = Arc::new(DingTalkSender::new(dingtalk_url));
-    let waterbot = WaterBot::new(WATERBOT_ID, sender, WATERBOT_RESET_HOUR);
-    let mut manager = ExecutorManager::new();
-    manager.set_executor(waterbot.id(), waterbot);
+    let manager = ExecutorManager::new();
+    manager.register(
+        WATERBOT_ID,
+        WaterBot::new(sender),
+        ExecutorMetadata::new("Reminds the DingTalk group to drink water"),
+    );
     manager
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+
+    // replies with the time it sees once `delay` elapsed
+    struct Clocked {
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl Executor for Clocked {
+        async fn execute(&self, ctx: &ExecutionContext) -> anyhow::Result<ExecutionOutcome> {
+            tokio::time::sleep(self.delay).await;
+            Ok(ExecutionOutcome {
+                executor: "clocked".into(),
+                content: ctx.now().format("%H:%M").to_string(),
+                ..Default::default()
+            })
+        }
+    }
+
+    struct FixedClock;
+
+    impl crate::Clock for FixedClock {
+        fn now(&self) -> chrono::DateTime<Local> {
+            Local.with_ymd_and_hms(2024, 9, 2, 18, 0, 0).unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_context() -> anyhow::Result<()> {
+        let manager = ExecutorManager::new();
+        let fast = Clocked {
+            delay: Duration::ZERO,
+        };
+        let slow = Clocked {
+            delay: Duration::from_secs(60),
+        };
+        manager.register(1, fast, Default::default());
+        manager.register(2, slow, Default::default());
+        let ctx = ExecutionContext::new(7, Local::now()).with_clock(Arc::new(FixedClock));
+        assert_eq!("18:00", manager.execute(1, &ctx).await?.content);
+        let err = manager.execute(3, &ctx).await.unwrap_err();
+        assert_eq!(
+            Some(&ExecutorNotFound(3.into())),
+            err.downcast_ref::<ExecutorNotFound>()
+        );
+
+        ctx.cancellation().cancel();
+        let err = manager.execute(2, &ctx).await.unwrap_err();
+        assert_eq!("execution of executor 2 cancelled", err.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_register() {
+        let manager = ExecutorManager::new();
+        let clocked = || Clocked {
+            delay: Duration::ZERO,
+        };
+        assert_eq!(
+            None,
+            manager.register("clock", clocked(), ExecutorMetadata::new("v1"))
+        );
+        let metadata = ExecutorMetadata::new("v2").with_parameters(serde_json::json!({
+            "type": "object",
+        }));
+        let replaced = manager.register("clock", clocked(), metadata.clone());
+        assert_eq!(Some(ExecutorMetadata::new("v1")), replaced);
+        manager.register(7, clocked(), Default::default());
+
+        assert!(manager.contains(7) && !manager.contains("7"));
+        assert_eq!(Some(metadata.clone()), manager.metadata("clock"));
+        let ids = manager
+            .list()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![ExecutorId::Number(7), ExecutorId::Name("clock".into())],
+            ids
+        );
+
+        assert_eq!(Some(metadata), manager.remove("clock"));
+        assert!(!manager.contains("clock"));
+        assert_eq!(None, manager.remove("clock"));
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::WebhookError;
+
+/// What an executor delivered, kept in the execution history.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExecutionOutcome {
+    /// Name of the executor, e.g. `waterbot`.
+    pub executor: String,
+    /// Platform the content was sent to, e.g. `dingtalk`.
+    pub sender: String,
+    pub content: String,
+    /// Status code replied by the platform.
+    pub response_code: Option<i64>,
+    pub response_message: Option<String>,
+}
+
+impl ExecutionOutcome {
+    /// Content the sender failed to deliver, with the code and message of the
+    /// platform when it rejected it.
+    pub fn undelivered(
+        executor: &str,
+        sender: &str,
+        content: &str,
+        err: &anyhow::Error,
+    ) -> ExecutionOutcome {
+        let rejected = err.downcast_ref::<WebhookError>();
+        ExecutionOutcome {
+            executor: executor.into(),
+            sender: sender.into(),
+            content: content.into(),
+            response_code: rejected.map(|err| err.code),
+            response_message: rejected.map(|err| err.message.clone()),
+        }
+    }
+
+    /// Attach the outcome to the error of the execution that got to it, see
+    /// [`ExecutionOutcome::of_error`].
+    pub fn attach_to(self, err: anyhow::Error) -> anyhow::Error {
+        err.context(Undelivered(self))
+    }
+
+    /// The outcome attached to the error of a failed execution.
+    pub fn of_error(err: &anyhow::Error) -> Option<&ExecutionOutcome> {
+        err.downcast_ref::<Undelivered>()
+            .map(|undelivered| &undelivered.0)
+    }
+}
+
+#[derive(Debug)]
+struct Undelivered(ExecutionOutcome);
+
+impl fmt::Display for Undelivered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} failed to deliver to {}",
+            self.0.executor, self.0.sender
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undelivered() {
+        let err = WebhookError::new("dingtalk", 310000, "keywords not in content").into();
+        let outcome = ExecutionOutcome::undelivered("waterbot", "dingtalk", "drink water", &err);
+        let err = outcome.attach_to(err);
+        assert_eq!(
+            "waterbot failed to deliver to dingtalk: dingtalk error 310000: keywords not in content",
+            format!("{:#}", err)
+        );
+        let outcome = ExecutionOutcome::of_error(&err).unwrap();
+        assert_eq!("drink water", outcome.content);
+        assert_eq!(Some(310000), outcome.response_code);
+        assert_eq!(
+            Some("keywords not in content"),
+            outcome.response_message.as_deref()
+        );
+        assert!(ExecutionOutcome::of_error(&anyhow::anyhow!("timeout")).is_none());
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Key an executor is registered under. Tasks refer to executors by their
+/// numeric event id, library users may pick names instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ExecutorId {
+    Number(i64),
+    Name(String),
+}
+
+impl fmt::Display for ExecutorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutorId::Number(id) => write!(f, "{}", id),
+            ExecutorId::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl From<i32> for ExecutorId {
+    fn from(value: i32) -> Self {
+        ExecutorId::Number(value.into())
+    }
+}
+
+impl From<i64> for ExecutorId {
+    fn from(value: i64) -> Self {
+        ExecutorId::Number(value)
+    }
+}
+
+impl From<usize> for ExecutorId {
+    fn from(value: usize) -> Self {
+        ExecutorId::Number(value as i64)
+    }
+}
+
+impl From<&str> for ExecutorId {
+    fn from(value: &str) -> Self {
+        ExecutorId::Name(value.into())
+    }
+}
+
+impl From<String> for ExecutorId {
+    fn from(value: String) -> Self {
+        ExecutorId::Name(value)
+    }
+}
+
+/// Describes a registered executor to the people binding tasks to it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExecutorMetadata {
+    pub description: String,
+    /// JSON schema of the payload the executor expects, `null` when it takes
+    /// no parameters.
+    #[serde(default)]
+    pub parameters: serde_json::Value,
+}
+
+impl ExecutorMetadata {
+    pub fn new(description: &str) -> ExecutorMetadata {
+        ExecutorMetadata {
+            description: description.into(),
+            parameters: serde_json::Value::Null,
+        }
+    }
+
+    pub fn with_parameters(mut self, schema: serde_json::Value) -> Self {
+        self.parameters = schema;
+        self
+    }
+}
+
+/// Returned when no executor is registered under the requested id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutorNotFound(pub ExecutorId);
+
+impl fmt::Display for ExecutorNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "executor {} not found", self.0)
+    }
+}
+
+impl std::error::Error for ExecutorNotFound {}
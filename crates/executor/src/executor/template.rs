@@ -0,0 +1,348 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Datelike;
+use minijinja::{context, Environment};
+use serde::Deserialize;
+
+use crate::{Button, Link, Mention, Message, MessageType, Sender};
+
+use super::{
+    context::ExecutionContext, counter::PeriodicCounter, outcome::ExecutionOutcome, Executor,
+};
+
+// name of the counter templates see as `counters.round`
+pub(crate) static ROUND_COUNTER: &str = "round";
+
+/// Content template in minijinja syntax. Templates see:
+///
+/// - `task.id`, `task.name`, `task.description`
+/// - `sequence`: the 1-based occurrence of the task, `attempt`: of the run
+/// - `date` (`2024-09-02`), `time` (`10:40`), `weekday` (1 for Monday) and
+///   `weekday_name` of the scheduled time
+/// - `counters.round`: the round in the period of the executor's counter
+/// - `payload`: everything the task was dispatched with
+///
+/// Messages also mention the `mentions` of the payload, e.g. the person on
+/// duty, as a list like `["all", {"mobile": "13800000000"}, {"user": "id"}]`.
+#[derive(Debug, Clone)]
+pub(crate) struct Template {
+    source: String,
+}
+
+impl Template {
+    /// Fails when the template does not parse.
+    pub fn new(source: &str) -> anyhow::Result<Template> {
+        Environment::new().template_from_str(source)?;
+        Ok(Template {
+            source: source.into(),
+        })
+    }
+
+    /// The text outside of expressions, statements and comments, what every
+    /// rendering contains regardless of the variables.
+    pub fn literal_text(&self) -> String {
+        let mut text = String::new();
+        let mut rest = self.source.as_str();
+        while let Some(start) = rest.find('{') {
+            let close = match rest[start..].get(..2) {
+                Some("{{") => "}}",
+                Some("{%") => "%}",
+                Some("{#") => "#}",
+                _ => {
+                    text.push_str(&rest[..=start]);
+                    rest = &rest[start + 1..];
+                    continue;
+                }
+            };
+            text.push_str(&rest[..start]);
+            rest = match rest[start..].find(close) {
+                Some(end) => &rest[start + end + 2..],
+                None => "",
+            };
+        }
+        text.push_str(rest);
+        text
+    }
+
+    pub fn render(&self, ctx: &ExecutionContext, round: i64) -> anyhow::Result<String> {
+        let payload = ctx.payload();
+        let scheduled_at = ctx.scheduled_at();
+        let content = Environment::new().render_str(
+            &self.source,
+            context! {
+                task => context! {
+                    id => ctx.task_id(),
+                    name => payload.get("name"),
+                    description => payload.get("description"),
+                },
+                sequence => payload.get("sequence"),
+                attempt => ctx.attempt(),
+                date => scheduled_at.format("%Y-%m-%d").to_string(),
+                time => scheduled_at.format("%H:%M").to_string(),
+                weekday => scheduled_at.weekday().number_from_monday(),
+                weekday_name => scheduled_at.format("%A").to_string(),
+                counters => context! { round },
+                payload => payload,
+            },
+        )?;
+        Ok(content)
+    }
+}
+
+/// How the rendered content is sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageFormat {
+    #[default]
+    Text,
+    /// Markdown where the platform supports it, text elsewhere.
+    Markdown,
+}
+
+/// Templates of the parts of a message. Senders pick the message type the
+/// parts fit, DingTalk e.g. sends an actionCard when there are buttons.
+#[derive(Debug, Clone)]
+pub(crate) struct MessageTemplate {
+    content: Template,
+    format: MessageFormat,
+    msgtype: Option<MessageType>,
+    title: Option<Template>,
+    links: Vec<LinkTemplate>,
+    // title and url of each button
+    buttons: Vec<(Template, Template)>,
+    mentions: Vec<Mention>,
+}
+
+/// Templates of the parts of a [`Link`].
+#[derive(Debug, Clone)]
+pub(crate) struct LinkTemplate {
+    pub title: Template,
+    pub url: Template,
+    pub description: Option<Template>,
+    pub image_url: Option<Template>,
+}
+
+impl MessageTemplate {
+    pub fn new(content: Template) -> Self {
+        MessageTemplate {
+            content,
+            format: MessageFormat::Text,
+            msgtype: None,
+            title: None,
+            links: vec![],
+            buttons: vec![],
+            mentions: vec![],
+        }
+    }
+    pub fn with_content(mut self, content: Template) -> Self {
+        self.content = content;
+        self
+    }
+    pub fn with_format(mut self, format: MessageFormat) -> Self {
+        self.format = format;
+        self
+    }
+    /// Message type of the platform to send, picked from the parts otherwise.
+    pub fn with_msgtype(mut self, msgtype: MessageType) -> Self {
+        self.msgtype = Some(msgtype);
+        self
+    }
+    pub fn with_title(mut self, title: Template) -> Self {
+        self.title = Some(title);
+        self
+    }
+    pub fn with_link(mut self, link: LinkTemplate) -> Self {
+        self.links.push(link);
+        self
+    }
+    pub fn with_button(mut self, title: Template, url: Template) -> Self {
+        self.buttons.push((title, url));
+        self
+    }
+    /// Mentioned by every message, on top of the payload's `mentions`.
+    pub fn with_mention(mut self, mention: Mention) -> Self {
+        self.mentions.push(mention);
+        self
+    }
+
+    pub fn render(&self, ctx: &ExecutionContext, round: i64) -> anyhow::Result<Message> {
+        let content = self.content.render(ctx, round)?;
+        let mut message = match self.format {
+            MessageFormat::Text => Message::text(&content),
+            MessageFormat::Markdown => Message {
+                text: content.clone(),
+                markdown: Some(content),
+                ..Default::default()
+            },
+        };
+        message.msgtype = self.msgtype;
+        if let Some(title) = &self.title {
+            message.title = Some(title.render(ctx, round)?);
+        }
+        for link in &self.links {
+            let render = |template: &Option<Template>| {
+                template
+                    .as_ref()
+                    .map(|template| template.render(ctx, round))
+                    .transpose()
+            };
+            message = message.with_link(Link {
+                title: link.title.render(ctx, round)?,
+                url: link.url.render(ctx, round)?,
+                description: render(&link.description)?,
+                image_url: render(&link.image_url)?,
+            });
+        }
+        for (title, url) in &self.buttons {
+            message = message.with_button(Button {
+                title: title.render(ctx, round)?,
+                url: url.render(ctx, round)?,
+            });
+        }
+        let mentions = match ctx.payload().get("mentions") {
+            Some(mentions) => serde_json::from_value::<Vec<Mention>>(mentions.clone())
+                .map_err(|err| anyhow::anyhow!("invalid mentions in payload: {}", err))?,
+            None => vec![],
+        };
+        message.mentions = self.mentions.iter().cloned().chain(mentions).collect();
+        Ok(message)
+    }
+}
+
+/// Sends content rendered from a template, new reminders need a template
+/// rather than code. A `template` in the payload, e.g. stored with the task,
+/// replaces the configured one.
+pub(crate) struct TemplateBot {
+    template: MessageTemplate,
+    counter: PeriodicCounter,
+    sender: Arc<dyn Sender>,
+}
+
+impl TemplateBot {
+    pub fn new(template: MessageTemplate, sender: Arc<dyn Sender>) -> Self {
+        TemplateBot {
+            template,
+            counter: PeriodicCounter::new(ROUND_COUNTER),
+            sender,
+        }
+    }
+    /// Counter behind `counters.round`, daily from midnight by default.
+    pub fn with_counter(mut self, counter: PeriodicCounter) -> Self {
+        self.counter = counter;
+        self
+    }
+}
+
+#[async_trait]
+impl Executor for TemplateBot {
+    async fn execute(&self, ctx: &ExecutionContext) -> anyhow::Result<ExecutionOutcome> {
+        let template = match ctx.payload().get("template").and_then(|t| t.as_str()) {
+            Some(source) => self.template.clone().with_content(Template::new(source)?),
+            None => self.template.clone(),
+        };
+        let (content, receipt) = self
+            .counter
+            .count(ctx, |round| async move {
+                let message = template.render(ctx, round)?;
+                let receipt = self.sender.send(&message).await.map_err(|err| {
+                    let sender = self.sender.platform();
+                    ExecutionOutcome::undelivered("template", sender, &message.text, &err)
+                        .attach_to(err)
+                })?;
+                Ok((message.text, receipt))
+            })
+            .await?;
+        Ok(ExecutionOutcome {
+            executor: "template".into(),
+            sender: receipt.platform,
+            content,
+            response_code: receipt.code,
+            response_message: receipt.message,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_render() -> anyhow::Result<()> {
+        let scheduled_at = Local.with_ymd_and_hms(2024, 9, 2, 10, 40, 0).unwrap();
+        let ctx = ExecutionContext::new(3, scheduled_at).with_payload(json!({
+            "name": "standup",
+            "description": "daily standup",
+            "sequence": 5,
+            "room": "B2",
+        }));
+        let template = Template::new(
+            "{{ task.name }}#{{ task.id }} {{ sequence }} {{ date }} {{ time }} \
+             {{ weekday }}/{{ weekday_name }} round {{ counters.round }} in {{ payload.room }}",
+        )?;
+        assert_eq!(
+            "standup#3 5 2024-09-02 10:40 1/Monday round 2 in B2",
+            template.render(&ctx, 2)?
+        );
+        assert!(Template::new("{{ task.name ").is_err());
+        assert_eq!(
+            "round {} of  done",
+            Template::new("round {} of {{ counters.round }} {% if true %}done{% endif %}{# x #}")?
+                .literal_text()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_message() -> anyhow::Result<()> {
+        let ctx = ExecutionContext::new(3, Local::now()).with_payload(json!({
+            "name": "water",
+            "mentions": [{ "mobile": "13800000000" }],
+        }));
+        let template = MessageTemplate::new(Template::new("**round {{ counters.round }}**")?)
+            .with_format(MessageFormat::Markdown)
+            .with_title(Template::new("{{ task.name }}")?)
+            .with_button(
+                Template::new("I drank it ✅")?,
+                Template::new("https://example.com/{{ task.id }}/{{ counters.round }}")?,
+            )
+            .with_mention(Mention::All)
+            .with_link(LinkTemplate {
+                title: Template::new("Why {{ task.name }}")?,
+                url: Template::new("https://example.com/why")?,
+                description: None,
+                image_url: Some(Template::new(
+                    "https://example.com/{{ counters.round }}.png",
+                )?),
+            })
+            .with_msgtype(MessageType::FeedCard);
+        let message = template.render(&ctx, 2)?;
+        assert_eq!(Some("water".into()), message.title);
+        assert_eq!(Some(MessageType::FeedCard), message.msgtype);
+        assert_eq!(
+            vec![Link {
+                title: "Why water".into(),
+                url: "https://example.com/why".into(),
+                description: None,
+                image_url: Some("https://example.com/2.png".into()),
+            }],
+            message.links
+        );
+        assert_eq!(Some("**round 2**".into()), message.markdown);
+        assert_eq!(
+            vec![Button {
+                title: "I drank it ✅".into(),
+                url: "https://example.com/3/2".into(),
+            }],
+            message.buttons
+        );
+        assert_eq!(
+            vec![Mention::All, Mention::Mobile("13800000000".into())],
+            message.mentions
+        );
+        Ok(())
+    }
+}
@@ -1,49 +1,67 @@
-use std::{cell::Cell, sync::Arc};
+use std::sync::Arc;
 
-use chrono::{Local, Timelike};
-use tokio::runtime::Builder;
+use async_trait::async_trait;
 
-use crate::sender::dingtalk::DingTalkSender;
+use crate::Sender;
 
-use super::Executor;
+use super::{
+    context::ExecutionContext,
+    counter::PeriodicCounter,
+    outcome::ExecutionOutcome,
+    template::{MessageTemplate, Template, ROUND_COUNTER},
+    Executor,
+};
+
+pub(crate) static DEFAULT_TEMPLATE: &str = "大家好，我是本群的【喝水提醒小助手】，这是今天的第{{ counters.round }}轮，希望此刻看到消息的小伙伴可以和我一起喝一杯水，一小时后我会继续提醒大家喝水，和我一起成为一天喝8杯水的人！";
 
 pub struct WaterBot {
-    id: usize,
-    times: Cell<usize>,
-    sender: Arc<DingTalkSender>,
-    /// timepoint hour when to reset times(24 hours)
-    reset: u32,
+    counter: PeriodicCounter,
+    template: MessageTemplate,
+    sender: Arc<dyn Sender>,
 }
 
 impl WaterBot {
-    pub fn new(id: usize, sender: Arc<DingTalkSender>, reset: u32) -> Self {
+    pub fn new(sender: Arc<dyn Sender>) -> Self {
         WaterBot {
-            id,
-            times: Cell::new(0),
+            counter: PeriodicCounter::new(ROUND_COUNTER),
+            template: MessageTemplate::new(
+                Template::new(DEFAULT_TEMPLATE).expect("built-in template parses"),
+            ),
             sender,
-            reset,
         }
     }
-    pub fn id(&self) -> usize {
-        self.id
+    pub fn with_counter(mut self, counter: PeriodicCounter) -> Self {
+        self.counter = counter;
+        self
     }
-    fn build_content(&self) -> String {
-        let times = self.times.get() + 1;
-        format!("大家好，我是本群的【喝水提醒小助手】，这是今天的第{}轮，希望此刻看到消息的小伙伴可以和我一起喝一杯水，一小时后我会继续提醒大家喝水，和我一起成为一天喝8杯水的人！", times)
+    pub fn with_template(mut self, template: MessageTemplate) -> Self {
+        self.template = template;
+        self
     }
 }
 
+#[async_trait]
 impl Executor for WaterBot {
-    fn execute(&self) -> anyhow::Result<()> {
-        let runtime = Builder::new_current_thread().enable_all().build()?;
-        let current_times = self.times.get();
-        runtime.block_on(self.sender.send(&self.build_content()))?;
-        println!("{}", self.build_content());
-        if Local::now().hour().ge(&self.reset) {
-            self.times.set(0);
-        } else {
-            self.times.set(current_times + 1);
-        }
-        Ok(())
+    async fn execute(&self, ctx: &ExecutionContext) -> anyhow::Result<ExecutionOutcome> {
+        let (content, receipt) = self
+            .counter
+            .count(ctx, |round| async move {
+                let message = self.template.render(ctx, round)?;
+                let receipt = self.sender.send(&message).await.map_err(|err| {
+                    let sender = self.sender.platform();
+                    ExecutionOutcome::undelivered("waterbot", sender, &message.text, &err)
+                        .attach_to(err)
+                })?;
+                Ok((message.text, receipt))
+            })
+            .await?;
+        println!("{}", content);
+        Ok(ExecutionOutcome {
+            executor: "waterbot".into(),
+            sender: receipt.platform,
+            content,
+            response_code: receipt.code,
+            response_message: receipt.message,
+        })
     }
 }
@@ -1,12 +1,45 @@
+mod config;
 mod executor;
+mod retry;
 mod sender;
+mod service;
+mod state;
 
-pub use executor::{new_executor_manager, WATERBOT_ID};
+pub use config::{ChannelConfig, Config, ExecutorConfig, ExecutorKind, StateConfig};
+pub use executor::{
+    new_executor_manager, Clock, ExecutionContext, ExecutionOutcome, Executor, ExecutorId,
+    ExecutorManager, ExecutorMetadata, ExecutorNotFound, Period, PeriodicCounter, SystemClock,
+    WATERBOT_ID,
+};
+pub use retry::{is_retryable, RetryPolicy, Retryable};
+pub use sender::{
+    dingtalk::{
+        DingTalkActionCard, DingTalkAt, DingTalkButton, DingTalkErrorKind, DingTalkFeedCard,
+        DingTalkFeedLink, DingTalkLink, DingTalkMarkdown, DingTalkMessage, DingTalkRequestBody,
+        DingTalkSender, DingTalkText, KeywordInjection,
+    },
+    feishu::{
+        FeishuCard, FeishuCardAction, FeishuCardElement, FeishuCardHeader, FeishuMessage,
+        FeishuPlainText, FeishuPost, FeishuPostContent, FeishuPostElement, FeishuPostLocale,
+        FeishuSender, FeishuText,
+    },
+    wecom::{
+        WeComArticle, WeComImage, WeComMarkdown, WeComMessage, WeComNews, WeComSender, WeComText,
+    },
+    Button, DeliveryReceipt, Image, Link, Mention, Message, MessageType, RateLimiter, Sender,
+    WebhookError, WebhookReply,
+};
+pub use service::{
+    router, serve, shutdown_signal, ExecuteRequest, ExecuteResponse, ExecuteStatus, ExecutorClient,
+    RemoteError,
+};
+pub use state::{MemoryStateStore, MySqlStateStore, StateStore};
 
-#[test]
-fn test_executor() {
+#[tokio::test]
+async fn test_executor() {
     let executor = new_executor_manager("");
+    let ctx = ExecutionContext::new(0, chrono::Local::now());
     for _ in 0..8 {
-        executor.execute(WATERBOT_ID).unwrap();
+        executor.execute(WATERBOT_ID, &ctx).await.unwrap();
     }
 }
@@ -0,0 +1,326 @@
+use std::{
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use rand::Rng;
+
+use crate::{RemoteError, WebhookError};
+
+type Classifier = Arc<dyn Fn(&anyhow::Error) -> bool + Send + Sync>;
+
+// identifies a policy and its clones in the errors it gave up on
+static NEXT_POLICY_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// How often and how fast a failed operation is tried again.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    /// Share of the backoff randomly added or removed, 0 to 1.
+    jitter: f64,
+    retryable: Classifier,
+    id: usize,
+}
+
+impl RetryPolicy {
+    /// Up to `max_attempts` attempts, backing off from 1 second doubling up to
+    /// 1 minute with 20% jitter, retrying the errors [`is_retryable`] accepts.
+    pub fn new(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            multiplier: 2.0,
+            jitter: 0.2,
+            retryable: Arc::new(is_retryable),
+            id: NEXT_POLICY_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// A single attempt.
+    pub fn none() -> RetryPolicy {
+        RetryPolicy::new(1)
+    }
+
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Decides which errors are worth another attempt.
+    pub fn with_retryable<F>(mut self, retryable: F) -> Self
+    where
+        F: Fn(&anyhow::Error) -> bool + Send + Sync + 'static,
+    {
+        self.retryable = Arc::new(retryable);
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Whether the error of the given 1-based attempt should be retried. An
+    /// error this policy already gave up on is not, so nesting it around
+    /// itself does not multiply the attempts.
+    pub fn should_retry(&self, attempt: u32, err: &anyhow::Error) -> bool {
+        attempt < self.max_attempts && !self.gave_up_on(err) && (self.retryable)(err)
+    }
+
+    fn gave_up_on(&self, err: &anyhow::Error) -> bool {
+        err.downcast_ref::<Exhausted>()
+            .is_some_and(|exhausted| exhausted.policy == self.id)
+    }
+
+    /// Delay before the attempt following the given 1-based attempt.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(63) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let backoff = backoff.min(self.max_backoff.as_secs_f64());
+        let jitter = if self.jitter > 0.0 {
+            rand::thread_rng().gen_range(-self.jitter..=self.jitter)
+        } else {
+            0.0
+        };
+        Duration::from_secs_f64((backoff * (1.0 + jitter)).max(0.0))
+    }
+
+    /// Run `op` with the 1-based attempt number until it succeeds, fails
+    /// permanently or runs out of attempts. Errors keep their retryability for
+    /// the policies of callers, e.g. a run retried after a longer backoff.
+    pub async fn run<F, Fut, T>(&self, mut op: F) -> anyhow::Result<T>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match op(attempt).await {
+                Err(err) if self.should_retry(attempt, &err) => {
+                    let backoff = self.backoff(attempt);
+                    eprintln!(
+                        "Attempt {} failed, retry in {:?}: {:#}",
+                        attempt, backoff, err
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return self.exhausted(attempt, result),
+            }
+        }
+    }
+
+    /// Blocking version of [`RetryPolicy::run`].
+    pub fn run_blocking<F, T>(&self, mut op: F) -> anyhow::Result<T>
+    where
+        F: FnMut(u32) -> anyhow::Result<T>,
+    {
+        let mut attempt = 1;
+        loop {
+            match op(attempt) {
+                Err(err) if self.should_retry(attempt, &err) => {
+                    let backoff = self.backoff(attempt);
+                    eprintln!(
+                        "Attempt {} failed, retry in {:?}: {:#}",
+                        attempt, backoff, err
+                    );
+                    thread::sleep(backoff);
+                    attempt += 1;
+                }
+                result => return self.exhausted(attempt, result),
+            }
+        }
+    }
+
+    fn exhausted<T>(&self, attempt: u32, result: anyhow::Result<T>) -> anyhow::Result<T> {
+        match result {
+            Err(err) if attempt > 1 && (self.retryable)(&err) => Err(err.context(Exhausted {
+                policy: self.id,
+                attempts: attempt,
+            })),
+            result => result,
+        }
+    }
+}
+
+// context of an error the attempts of a policy ran out on
+#[derive(Debug)]
+struct Exhausted {
+    policy: usize,
+    attempts: u32,
+}
+
+impl fmt::Display for Exhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "gave up after {} attempts", self.attempts)
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("multiplier", &self.multiplier)
+            .field("jitter", &self.jitter)
+            .finish()
+    }
+}
+
+/// Errors telling whether another attempt may succeed.
+pub trait Retryable {
+    fn is_retryable(&self) -> bool;
+}
+
+/// Timeouts, connection errors, 429 and 5xx replies.
+impl Retryable for reqwest::Error {
+    fn is_retryable(&self) -> bool {
+        self.is_timeout()
+            || self.is_connect()
+            || self
+                .status()
+                .is_some_and(|status| status.is_server_error() || status.as_u16() == 429)
+    }
+}
+
+/// Transient failures: transport errors worth another attempt, platform
+/// errors reporting throttling or a busy system and executor service failures
+/// it reported as such.
+pub fn is_retryable(err: &anyhow::Error) -> bool {
+    if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+        return err.is_retryable();
+    }
+    if let Some(err) = err.downcast_ref::<WebhookError>() {
+        return err.is_retryable();
+    }
+    if let Some(err) = err.downcast_ref::<RemoteError>() {
+        return err.is_retryable();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::new(5)
+            .with_backoff(Duration::from_secs(1), Duration::from_secs(5))
+            .with_jitter(0.0);
+        assert_eq!(Duration::from_secs(1), policy.backoff(1));
+        assert_eq!(Duration::from_secs(2), policy.backoff(2));
+        assert_eq!(Duration::from_secs(4), policy.backoff(3));
+        assert_eq!(Duration::from_secs(5), policy.backoff(4));
+        assert_eq!(Duration::from_secs(5), policy.backoff(100));
+
+        let policy = policy.with_jitter(0.5);
+        for _ in 0..100 {
+            let backoff = policy.backoff(2);
+            assert!(backoff >= Duration::from_secs(1) && backoff <= Duration::from_secs(3));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let policy = RetryPolicy::new(3)
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(1))
+            .with_retryable(|err| err.to_string() == "busy");
+        let attempts = AtomicU32::new(0);
+        let result = policy
+            .run(|attempt| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    match attempt {
+                        1 | 2 => anyhow::bail!("busy"),
+                        _ => Ok(attempt),
+                    }
+                }
+            })
+            .await;
+        assert_eq!(3, result.unwrap());
+
+        attempts.store(0, Ordering::SeqCst);
+        let result: anyhow::Result<()> = policy
+            .run(|_| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async { anyhow::bail!("bad request") }
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(1, attempts.load(Ordering::SeqCst));
+
+        attempts.store(0, Ordering::SeqCst);
+        let result: anyhow::Result<()> = policy
+            .run(|_| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async { anyhow::bail!("busy") }
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+
+        // the policy does not try again what it ran out of attempts on, other
+        // policies still do
+        let policy =
+            RetryPolicy::new(2).with_backoff(Duration::from_millis(1), Duration::from_millis(1));
+        attempts.store(0, Ordering::SeqCst);
+        let err = policy
+            .run(|_| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                let inner = policy.clone();
+                async move {
+                    inner
+                        .run(|_| async {
+                            let err = WebhookError::new("dingtalk", -1, "system busy")
+                                .with_retryable(true);
+                            Err::<(), _>(anyhow::Error::from(err))
+                        })
+                        .await
+                }
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(1, attempts.load(Ordering::SeqCst));
+        assert_eq!(
+            "gave up after 2 attempts: dingtalk error -1: system busy",
+            format!("{:#}", err)
+        );
+        assert!(err.downcast_ref::<WebhookError>().is_some());
+        assert!(is_retryable(&err));
+        assert!(!policy.should_retry(1, &err));
+        assert!(RetryPolicy::new(2).should_retry(1, &err));
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(!is_retryable(&anyhow::anyhow!("unknown")));
+        let err = WebhookError::new("dingtalk", -1, "system busy").with_retryable(true);
+        assert!(is_retryable(&err.into()));
+        let err = WebhookError::new("dingtalk", 300001, "token is not exist");
+        assert!(!is_retryable(&err.into()));
+    }
+}
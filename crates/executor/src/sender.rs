@@ -1 +1,23 @@
 pub mod dingtalk;
+pub mod feishu;
+mod limiter;
+mod message;
+mod webhook;
+pub mod wecom;
+
+use async_trait::async_trait;
+
+pub use limiter::RateLimiter;
+pub use message::{Button, DeliveryReceipt, Image, Link, Mention, Message, MessageType};
+pub use webhook::{WebhookError, WebhookReply};
+
+/// Delivers messages to a chat platform.
+#[async_trait]
+pub trait Sender: Send + Sync {
+    /// Name of the platform, e.g. `dingtalk`.
+    fn platform(&self) -> &str;
+
+    /// Send the message, converting it to what the platform supports. Fails
+    /// when the platform rejects it.
+    async fn send(&self, message: &Message) -> anyhow::Result<DeliveryReceipt>;
+}
@@ -1,54 +1,174 @@
-use reqwest::{header::CONTENT_TYPE, Client};
-use serde::{Deserialize, Serialize};
+mod error;
+mod keyword;
+mod message;
 
-static MESSAGE_TYPE: &str = "text";
+use std::{sync::Arc, time::Duration};
 
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Local;
+use hmac::{Hmac, Mac};
+use reqwest::{header::CONTENT_TYPE, Url};
+use sha2::Sha256;
+
+use super::{
+    webhook::{Webhook, WebhookReply},
+    DeliveryReceipt, Message, RateLimiter, Sender,
+};
+
+pub use error::DingTalkErrorKind;
+pub use keyword::KeywordInjection;
+pub use message::{
+    DingTalkActionCard, DingTalkAt, DingTalkButton, DingTalkFeedCard, DingTalkFeedLink,
+    DingTalkLink, DingTalkMarkdown, DingTalkMessage, DingTalkRequestBody, DingTalkText,
+};
+
+static PLATFORM: &str = "dingtalk";
+// a robot takes at most 20 messages a minute
+static MESSAGES_PER_MINUTE: u32 = 20;
+
+/// Sends messages to a DingTalk group robot through its webhook.
 pub struct DingTalkSender {
-    client: Client,
+    webhook: Webhook,
     url: String,
+    secret: Option<String>,
+    keywords: Vec<String>,
+    keyword_injection: KeywordInjection,
 }
 
 impl DingTalkSender {
     pub fn new(url: &str) -> DingTalkSender {
-        let client = Client::new();
+        let limiter = RateLimiter::shared(url, MESSAGES_PER_MINUTE, Duration::from_secs(60));
         DingTalkSender {
-            client,
+            webhook: Webhook::new(PLATFORM, limiter, retryable),
             url: url.into(),
+            secret: None,
+            keywords: vec![],
+            keyword_injection: KeywordInjection::Footer,
+        }
+    }
+
+    /// Sign every request with the secret of a robot using the "加签" security
+    /// setting.
+    pub fn with_secret(mut self, secret: &str) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// Replace the limit of 20 messages a minute of the robot.
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.webhook.set_limiter(limiter);
+        self
+    }
+
+    /// Keywords of a robot using the "自定义关键词" security setting, messages
+    /// without any of them get one as a footer.
+    pub fn with_keywords(mut self, keywords: Vec<String>) -> Self {
+        self.keywords = keywords;
+        self
+    }
+
+    pub fn with_keyword_injection(mut self, injection: KeywordInjection) -> Self {
+        self.keyword_injection = injection;
+        self
+    }
+
+    /// The webhook with the `timestamp` and `sign` query parameters of the
+    /// given millisecond timestamp when a secret is set.
+    pub fn signed_url(&self, timestamp: i64) -> anyhow::Result<Url> {
+        let mut url = Url::parse(&self.url)?;
+        if let Some(secret) = &self.secret {
+            url.query_pairs_mut()
+                .append_pair("timestamp", &timestamp.to_string())
+                .append_pair("sign", &sign(secret, timestamp));
         }
+        Ok(url)
     }
 
-    pub async fn send(&self, content: &str) -> anyhow::Result<()> {
-        let reply = self
-            .client
-            .post(&self.url)
-            .header(CONTENT_TYPE, "application/json")
-            .json(&DingTalkRequestBody {
-                msgtype: MESSAGE_TYPE.into(),
-                text: Text {
-                    content: content.into(),
-                },
+    /// Send a request body built by hand, for the message types `Message`
+    /// does not cover such as feed cards.
+    pub async fn send_message(&self, body: &DingTalkRequestBody) -> anyhow::Result<WebhookReply> {
+        let mut body = body.clone();
+        body.message
+            .comply(&self.keywords, self.keyword_injection)?;
+        self.webhook
+            .post(|client| {
+                let url = self.signed_url(Local::now().timestamp_millis())?;
+                Ok(client
+                    .post(url)
+                    .header(CONTENT_TYPE, "application/json")
+                    .json(&body))
             })
-            .send()
-            .await?;
-        let reply = reply.json::<DingTalkReply>().await?;
-        println!("[{}]: {}", reply.errcode, reply.errmsg);
-        Ok(())
+            .await
     }
 }
 
-#[derive(Debug, Serialize)]
-pub struct DingTalkRequestBody {
-    pub msgtype: String,
-    pub text: Text,
+#[async_trait]
+impl Sender for DingTalkSender {
+    fn platform(&self) -> &str {
+        PLATFORM
+    }
+
+    async fn send(&self, message: &Message) -> anyhow::Result<DeliveryReceipt> {
+        let reply = self.send_message(&message.into()).await?;
+        Ok(self.webhook.receipt(reply))
+    }
 }
 
-#[derive(Debug, Serialize)]
-pub struct Text {
-    content: String,
+pub(super) fn retryable(errcode: i64) -> bool {
+    DingTalkErrorKind::of(errcode).is_retryable()
 }
 
-#[derive(Debug, Deserialize)]
-pub struct DingTalkReply {
-    pub errcode: usize,
-    pub errmsg: String,
+/// Base64 of the HMAC-SHA256 of `"{timestamp}\n{secret}"` keyed with the
+/// secret, as DingTalk documents it for signed robots.
+fn sign(secret: &str, timestamp: i64) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac takes keys of any length");
+    mac.update(format!("{}\n{}", timestamp, secret).as_bytes());
+    STANDARD.encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign() -> anyhow::Result<()> {
+        // the secret of the documentation's example, signed by its Python
+        // snippet (`quote_plus(b64encode(hmac_sha256(secret, "{ts}\n{secret}")))`)
+        // at a fixed timestamp since the example takes the current time
+        assert_eq!(
+            "hXZTWifRGHclNuZSKxoXc//sh51SWfVfRIhcdeKs63I=",
+            sign("this is secret", 1599360473000)
+        );
+        let sender =
+            DingTalkSender::new("https://oapi.dingtalk.com/robot/send?access_token=XXXXXX")
+                .with_secret("this is secret");
+        assert_eq!(
+            "https://oapi.dingtalk.com/robot/send?access_token=XXXXXX&timestamp=1599360473000\
+             &sign=hXZTWifRGHclNuZSKxoXc%2F%2Fsh51SWfVfRIhcdeKs63I%3D",
+            sender.signed_url(1599360473000)?.as_str()
+        );
+
+        // computed independently with the algorithm of DingTalk's documentation
+        assert_eq!(
+            "6WBrLyLsyv83fOoF9UxL8Vr4L2LehGOYX+16KmCIdro=",
+            sign("SEC000000000000000000000", 1600000000000)
+        );
+        assert_eq!(
+            "hQRxlNuFu9Pm1vAwxP7rAh+SQf85eVuUYWMuBVJQRIs=",
+            sign("SECtest", 1725244800000)
+        );
+
+        let url = "https://oapi.dingtalk.com/robot/send?access_token=token";
+        let sender = DingTalkSender::new(url);
+        assert_eq!(url, sender.signed_url(1725244800000)?.as_str());
+        let sender = sender.with_secret("SECtest");
+        assert_eq!(
+            "https://oapi.dingtalk.com/robot/send?access_token=token&timestamp=1725244800000\
+             &sign=hQRxlNuFu9Pm1vAwxP7rAh%2BSQf85eVuUYWMuBVJQRIs%3D",
+            sender.signed_url(1725244800000)?.as_str()
+        );
+        Ok(())
+    }
 }
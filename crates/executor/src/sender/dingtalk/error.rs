@@ -0,0 +1,56 @@
+/// What DingTalk's error codes mean for the sender.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DingTalkErrorKind {
+    /// `-1`, the system is busy.
+    Busy,
+    /// Over the 20 messages a minute of a robot: `130101`, `410100`, `660026`.
+    RateLimited,
+    /// Rejected by a security setting of the robot, a missing keyword, a bad
+    /// signature or an address outside the IP allowlist: `310000`.
+    Security,
+    /// The access token of the webhook does not exist: `300001`.
+    InvalidToken,
+    /// The request itself is malformed: `40035`, `40044`, `43004`.
+    InvalidMessage,
+    Unknown,
+}
+
+impl DingTalkErrorKind {
+    pub fn of(errcode: i64) -> DingTalkErrorKind {
+        match errcode {
+            -1 => DingTalkErrorKind::Busy,
+            130101 | 410100 | 660026 => DingTalkErrorKind::RateLimited,
+            310000 => DingTalkErrorKind::Security,
+            300001 => DingTalkErrorKind::InvalidToken,
+            40035 | 40044 | 43004 => DingTalkErrorKind::InvalidMessage,
+            _ => DingTalkErrorKind::Unknown,
+        }
+    }
+
+    /// Busy and rate limited sends may succeed later, everything else needs
+    /// the message or the robot fixed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            DingTalkErrorKind::Busy | DingTalkErrorKind::RateLimited
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(
+            DingTalkErrorKind::RateLimited,
+            DingTalkErrorKind::of(660026)
+        );
+        assert!(DingTalkErrorKind::of(660026).is_retryable());
+        assert_eq!(DingTalkErrorKind::Security, DingTalkErrorKind::of(310000));
+        assert!(!DingTalkErrorKind::of(310000).is_retryable());
+        assert!(DingTalkErrorKind::of(-1).is_retryable());
+        assert!(!DingTalkErrorKind::of(123456).is_retryable());
+    }
+}
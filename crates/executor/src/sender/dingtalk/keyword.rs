@@ -0,0 +1,120 @@
+use serde::Deserialize;
+
+use super::DingTalkMessage;
+
+/// What a sender does with messages containing none of the keywords of a robot
+/// using the "自定义关键词" security setting, which DingTalk would reject.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeywordInjection {
+    /// Put the first keyword in front of the content.
+    Prefix,
+    /// Add the first keyword as the last paragraph of the content.
+    #[default]
+    Footer,
+    /// Fail without sending.
+    Off,
+}
+
+impl DingTalkMessage {
+    /// Whether one of the keywords is in the text DingTalk checks: the titles
+    /// and contents of the message.
+    pub fn contains_keyword(&self, keywords: &[String]) -> bool {
+        self.texts().iter().any(|text| {
+            keywords
+                .iter()
+                .any(|keyword| text.contains(keyword.as_str()))
+        })
+    }
+
+    /// Make sure the message contains one of the keywords, adding the first
+    /// one where `injection` says. Fails when it is off.
+    pub fn comply(
+        &mut self,
+        keywords: &[String],
+        injection: KeywordInjection,
+    ) -> anyhow::Result<()> {
+        let Some(keyword) = keywords.first() else {
+            return Ok(());
+        };
+        if self.contains_keyword(keywords) {
+            return Ok(());
+        }
+        match (injection, self.content_mut()) {
+            (KeywordInjection::Prefix, Some(content)) => {
+                *content = format!("{} {}", keyword, content);
+            }
+            (KeywordInjection::Footer, Some(content)) => {
+                content.push_str("\n\n");
+                content.push_str(keyword);
+            }
+            _ => anyhow::bail!("message contains none of the keywords {:?}", keywords),
+        }
+        Ok(())
+    }
+
+    fn texts(&self) -> Vec<&str> {
+        match self {
+            DingTalkMessage::Text { text } => vec![&text.content],
+            DingTalkMessage::Markdown { markdown } => vec![&markdown.title, &markdown.text],
+            DingTalkMessage::Link { link } => vec![&link.title, &link.text],
+            DingTalkMessage::ActionCard { action_card } => {
+                vec![&action_card.title, &action_card.text]
+            }
+            DingTalkMessage::FeedCard { feed_card } => feed_card
+                .links
+                .iter()
+                .map(|link| link.title.as_str())
+                .collect(),
+        }
+    }
+
+    // the main text of the message, the title of the first link of a feedCard
+    fn content_mut(&mut self) -> Option<&mut String> {
+        match self {
+            DingTalkMessage::Text { text } => Some(&mut text.content),
+            DingTalkMessage::Markdown { markdown } => Some(&mut markdown.text),
+            DingTalkMessage::Link { link } => Some(&mut link.text),
+            DingTalkMessage::ActionCard { action_card } => Some(&mut action_card.text),
+            DingTalkMessage::FeedCard { feed_card } => {
+                feed_card.links.first_mut().map(|link| &mut link.title)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comply() {
+        let keywords = vec!["提醒".to_string(), "reminder".to_string()];
+        let mut message = DingTalkMessage::markdown("喝水提醒", "drink water");
+        message.comply(&keywords, KeywordInjection::Off).unwrap();
+        assert_eq!(
+            DingTalkMessage::markdown("喝水提醒", "drink water"),
+            message
+        );
+
+        let mut message = DingTalkMessage::text("drink water");
+        message.comply(&keywords, KeywordInjection::Footer).unwrap();
+        assert_eq!(DingTalkMessage::text("drink water\n\n提醒"), message);
+
+        let mut message = DingTalkMessage::text("drink water");
+        message.comply(&keywords, KeywordInjection::Prefix).unwrap();
+        assert_eq!(DingTalkMessage::text("提醒 drink water"), message);
+
+        let mut message = DingTalkMessage::text("drink water");
+        let err = message
+            .comply(&keywords, KeywordInjection::Off)
+            .unwrap_err();
+        assert_eq!(
+            "message contains none of the keywords [\"提醒\", \"reminder\"]",
+            err.to_string()
+        );
+        assert!(DingTalkMessage::text("x")
+            .comply(&[], KeywordInjection::Off)
+            .is_ok());
+    }
+}
@@ -0,0 +1,528 @@
+use serde::Serialize;
+
+use crate::{Image, Link, Mention, Message, MessageType};
+
+/// What a robot posts to its webhook: a message and the people it mentions.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DingTalkRequestBody {
+    #[serde(flatten)]
+    pub message: DingTalkMessage,
+    #[serde(skip_serializing_if = "DingTalkAt::is_empty")]
+    pub at: DingTalkAt,
+}
+
+/// The `at` section, only text and markdown messages notify.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DingTalkAt {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub at_mobiles: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub at_user_ids: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_at_all: bool,
+}
+
+impl DingTalkAt {
+    pub fn is_empty(&self) -> bool {
+        self.at_mobiles.is_empty() && self.at_user_ids.is_empty() && !self.is_at_all
+    }
+}
+
+impl DingTalkRequestBody {
+    pub fn new(message: DingTalkMessage) -> DingTalkRequestBody {
+        DingTalkRequestBody {
+            message,
+            at: DingTalkAt::default(),
+        }
+    }
+
+    /// Mention someone. DingTalk only highlights mobiles and user ids that
+    /// appear as `@xxx` in the content, missing ones are appended to it.
+    pub fn with_mention(mut self, mention: &Mention) -> Self {
+        let (ids, id) = match mention {
+            Mention::All => {
+                self.at.is_at_all = true;
+                return self;
+            }
+            Mention::Mobile(mobile) => (&mut self.at.at_mobiles, mobile),
+            Mention::User(user) => (&mut self.at.at_user_ids, user),
+        };
+        if !ids.contains(id) {
+            ids.push(id.clone());
+        }
+        let placeholder = format!("@{}", id);
+        if let DingTalkMessage::Text {
+            text: DingTalkText { content: text },
+        }
+        | DingTalkMessage::Markdown {
+            markdown: DingTalkMarkdown { text, .. },
+        } = &mut self.message
+        {
+            if !contains_mention(text, &placeholder) {
+                text.push(' ');
+                text.push_str(&placeholder);
+            }
+        }
+        self
+    }
+}
+
+impl From<DingTalkMessage> for DingTalkRequestBody {
+    fn from(message: DingTalkMessage) -> Self {
+        DingTalkRequestBody::new(message)
+    }
+}
+
+// whether `@id` stands on its own in the text, `@138` is not in `@13800000000`
+fn contains_mention(text: &str, placeholder: &str) -> bool {
+    let is_id = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    text.match_indices(placeholder).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + placeholder.len()..].chars().next();
+        !before.is_some_and(is_id) && !after.is_some_and(is_id)
+    })
+}
+
+/// Messages mentioning people are sent as markdown when the type picked for
+/// them does not notify anyone, with the buttons as links.
+impl From<&Message> for DingTalkRequestBody {
+    fn from(message: &Message) -> Self {
+        let mut dingtalk = DingTalkMessage::from(message);
+        if !message.mentions.is_empty() && !dingtalk.notifies() {
+            let mut text = markdown(message);
+            for button in &message.buttons {
+                text.push_str(&format!("\n\n[{}]({})", button.title, button.url));
+            }
+            dingtalk = DingTalkMessage::markdown(&title(message), &text);
+        }
+        message
+            .mentions
+            .iter()
+            .fold(dingtalk.into(), |body, mention| body.with_mention(mention))
+    }
+}
+
+/// A message of one of the types DingTalk robots send.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "msgtype", rename_all = "camelCase")]
+pub enum DingTalkMessage {
+    Text {
+        text: DingTalkText,
+    },
+    Markdown {
+        markdown: DingTalkMarkdown,
+    },
+    Link {
+        link: DingTalkLink,
+    },
+    ActionCard {
+        #[serde(rename = "actionCard")]
+        action_card: DingTalkActionCard,
+    },
+    FeedCard {
+        #[serde(rename = "feedCard")]
+        feed_card: DingTalkFeedCard,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DingTalkText {
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DingTalkMarkdown {
+    /// Shown in the conversation list, not in the message.
+    pub title: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DingTalkLink {
+    pub title: String,
+    pub text: String,
+    pub message_url: String,
+    pub pic_url: String,
+}
+
+/// Markdown card with either a single button or several ones.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DingTalkActionCard {
+    pub title: String,
+    pub text: String,
+    #[serde(rename = "singleTitle", skip_serializing_if = "Option::is_none")]
+    pub single_title: Option<String>,
+    #[serde(rename = "singleURL", skip_serializing_if = "Option::is_none")]
+    pub single_url: Option<String>,
+    /// `0` stacks the buttons vertically, `1` places them side by side.
+    #[serde(rename = "btnOrientation")]
+    pub btn_orientation: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub btns: Vec<DingTalkButton>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DingTalkButton {
+    pub title: String,
+    #[serde(rename = "actionURL")]
+    pub action_url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DingTalkFeedCard {
+    pub links: Vec<DingTalkFeedLink>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DingTalkFeedLink {
+    pub title: String,
+    #[serde(rename = "messageURL")]
+    pub message_url: String,
+    #[serde(rename = "picURL")]
+    pub pic_url: String,
+}
+
+impl DingTalkMessage {
+    pub fn text(content: &str) -> DingTalkMessage {
+        DingTalkMessage::Text {
+            text: DingTalkText {
+                content: content.into(),
+            },
+        }
+    }
+
+    pub fn markdown(title: &str, text: &str) -> DingTalkMessage {
+        DingTalkMessage::Markdown {
+            markdown: DingTalkMarkdown {
+                title: title.into(),
+                text: text.into(),
+            },
+        }
+    }
+
+    pub fn link(title: &str, text: &str, message_url: &str, pic_url: &str) -> DingTalkMessage {
+        DingTalkMessage::Link {
+            link: DingTalkLink {
+                title: title.into(),
+                text: text.into(),
+                message_url: message_url.into(),
+                pic_url: pic_url.into(),
+            },
+        }
+    }
+
+    /// A single button becomes the whole-card `singleTitle`/`singleURL`,
+    /// several ones are laid out side by side.
+    pub fn action_card(title: &str, text: &str, buttons: Vec<DingTalkButton>) -> DingTalkMessage {
+        let mut card = DingTalkActionCard {
+            title: title.into(),
+            text: text.into(),
+            single_title: None,
+            single_url: None,
+            btn_orientation: "1".into(),
+            btns: vec![],
+        };
+        match <[DingTalkButton; 1]>::try_from(buttons) {
+            Ok([button]) => {
+                card.single_title = Some(button.title);
+                card.single_url = Some(button.action_url);
+            }
+            Err(buttons) => card.btns = buttons,
+        }
+        DingTalkMessage::ActionCard { action_card: card }
+    }
+
+    pub fn feed_card(links: Vec<DingTalkFeedLink>) -> DingTalkMessage {
+        DingTalkMessage::FeedCard {
+            feed_card: DingTalkFeedCard { links },
+        }
+    }
+
+    // only text and markdown messages notify the people in `at`
+    fn notifies(&self) -> bool {
+        matches!(
+            self,
+            DingTalkMessage::Text { .. } | DingTalkMessage::Markdown { .. }
+        )
+    }
+}
+
+/// Uses the `msgtype` of the message when it has what the type needs,
+/// otherwise picks the richest type the message fits: buttons make an
+/// actionCard, a single link without markdown a link, markdown or several links
+/// a markdown message with the links appended and anything else text.
+impl From<&Message> for DingTalkMessage {
+    fn from(message: &Message) -> Self {
+        let title = title(message);
+        match (message.msgtype, message.links.as_slice()) {
+            (Some(MessageType::Text), _) => DingTalkMessage::text(&message.plain_text()),
+            (Some(MessageType::Markdown), _) => {
+                DingTalkMessage::markdown(&title, &markdown(message))
+            }
+            (Some(MessageType::Link), [link, ..]) => link_card(message, link),
+            (Some(MessageType::FeedCard), [_, ..]) => DingTalkMessage::feed_card(
+                message
+                    .links
+                    .iter()
+                    .map(|link| DingTalkFeedLink {
+                        title: link.title.clone(),
+                        message_url: link.url.clone(),
+                        pic_url: link.image_url.clone().unwrap_or_default(),
+                    })
+                    .collect(),
+            ),
+            _ if !message.buttons.is_empty() => {
+                let buttons = message
+                    .buttons
+                    .iter()
+                    .map(|button| DingTalkButton {
+                        title: button.title.clone(),
+                        action_url: button.url.clone(),
+                    })
+                    .collect();
+                DingTalkMessage::action_card(&title, &markdown(message), buttons)
+            }
+            (_, []) if message.markdown.is_none() => DingTalkMessage::text(&message.plain_text()),
+            (_, [link]) if message.markdown.is_none() => link_card(message, link),
+            _ => DingTalkMessage::markdown(&title, &markdown(message)),
+        }
+    }
+}
+
+// the title of the message or the first line of its text
+fn title(message: &Message) -> String {
+    message
+        .title
+        .clone()
+        .unwrap_or_else(|| message.text.lines().next().unwrap_or_default().into())
+}
+
+fn link_card(message: &Message, link: &Link) -> DingTalkMessage {
+    DingTalkMessage::link(
+        &link.title,
+        link.description.as_deref().unwrap_or(&message.text),
+        &link.url,
+        link.image_url.as_deref().unwrap_or_default(),
+    )
+}
+
+// markdown of the message with its links and image urls appended
+fn markdown(message: &Message) -> String {
+    let mut lines = vec![message
+        .markdown
+        .clone()
+        .unwrap_or_else(|| message.text.clone())];
+    for link in &message.links {
+        lines.push(format!("[{}]({})", link.title, link.url));
+    }
+    for image in &message.images {
+        if let Image::Url(url) = image {
+            lines.push(format!("![]({})", url));
+        }
+    }
+    lines.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::Button;
+
+    use super::*;
+
+    #[test]
+    fn test_request_json() -> anyhow::Result<()> {
+        let card = DingTalkMessage::action_card(
+            "Water",
+            "**Round 3**",
+            vec![
+                DingTalkButton {
+                    title: "I drank it ✅".into(),
+                    action_url: "https://example.com/drank".into(),
+                },
+                DingTalkButton {
+                    title: "Later".into(),
+                    action_url: "https://example.com/later".into(),
+                },
+            ],
+        );
+        assert_eq!(
+            json!({
+                "msgtype": "actionCard",
+                "actionCard": {
+                    "title": "Water",
+                    "text": "**Round 3**",
+                    "btnOrientation": "1",
+                    "btns": [
+                        { "title": "I drank it ✅", "actionURL": "https://example.com/drank" },
+                        { "title": "Later", "actionURL": "https://example.com/later" },
+                    ],
+                },
+            }),
+            serde_json::to_value(&card)?
+        );
+        let link = DingTalkMessage::link("Why", "Stay hydrated", "https://example.com", "");
+        assert_eq!(
+            json!({
+                "msgtype": "link",
+                "link": {
+                    "title": "Why",
+                    "text": "Stay hydrated",
+                    "messageUrl": "https://example.com",
+                    "picUrl": "",
+                },
+            }),
+            serde_json::to_value(&link)?
+        );
+        let feed = DingTalkMessage::feed_card(vec![DingTalkFeedLink {
+            title: "Why".into(),
+            message_url: "https://example.com".into(),
+            pic_url: "https://example.com/cup.png".into(),
+        }]);
+        assert_eq!(
+            json!({
+                "msgtype": "feedCard",
+                "feedCard": {
+                    "links": [{
+                        "title": "Why",
+                        "messageURL": "https://example.com",
+                        "picURL": "https://example.com/cup.png",
+                    }],
+                },
+            }),
+            serde_json::to_value(&feed)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mentions() -> anyhow::Result<()> {
+        let message = Message::text("you are on duty")
+            .with_mention(Mention::Mobile("13800000000".into()))
+            .with_mention(Mention::User("manager1234".into()))
+            .with_mention(Mention::All);
+        assert_eq!(
+            json!({
+                "msgtype": "text",
+                "text": { "content": "you are on duty @13800000000 @manager1234" },
+                "at": {
+                    "atMobiles": ["13800000000"],
+                    "atUserIds": ["manager1234"],
+                    "isAtAll": true,
+                },
+            }),
+            serde_json::to_value(DingTalkRequestBody::from(&message))?
+        );
+
+        // placeholders already in the content stay where they are
+        let message = Message::markdown("Duty", "**@13800000000** is on duty", "")
+            .with_mention(Mention::Mobile("13800000000".into()));
+        let body = DingTalkRequestBody::from(&message);
+        assert_eq!(
+            DingTalkMessage::markdown("Duty", "**@13800000000** is on duty"),
+            body.message
+        );
+
+        // a longer mobile starting with the same digits is someone else
+        let message = Message::text("@13800000000 and").with_mention(Mention::Mobile("138".into()));
+        let body = DingTalkRequestBody::from(&message);
+        assert_eq!(DingTalkMessage::text("@13800000000 and @138"), body.message);
+        let message = Message::text("@138, you").with_mention(Mention::Mobile("138".into()));
+        let body = DingTalkRequestBody::from(&message);
+        assert_eq!(DingTalkMessage::text("@138, you"), body.message);
+
+        // cards notify no one, mentions turn them into markdown
+        let message = Message::text("on duty")
+            .with_button(Button {
+                title: "Done".into(),
+                url: "https://example.com/done".into(),
+            })
+            .with_mention(Mention::User("manager1234".into()));
+        let body = DingTalkRequestBody::from(&message);
+        assert_eq!(
+            DingTalkMessage::markdown(
+                "on duty",
+                "on duty\n\n[Done](https://example.com/done) @manager1234"
+            ),
+            body.message
+        );
+        assert_eq!(vec!["manager1234"], body.at.at_user_ids);
+
+        let body = DingTalkRequestBody::from(&Message::text("no one"));
+        assert_eq!(
+            json!({ "msgtype": "text", "text": { "content": "no one" } }),
+            serde_json::to_value(body)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_message() {
+        let message = Message::text("drink water");
+        assert_eq!(DingTalkMessage::text("drink water"), (&message).into());
+
+        let message = Message::markdown("Water", "**drink**", "drink");
+        assert_eq!(
+            DingTalkMessage::markdown("Water", "**drink**"),
+            (&message).into()
+        );
+
+        let message = message.with_button(Button {
+            title: "I drank it ✅".into(),
+            url: "https://example.com/drank".into(),
+        });
+        let DingTalkMessage::ActionCard { action_card } = (&message).into() else {
+            panic!("buttons make an action card");
+        };
+        assert_eq!(Some("I drank it ✅".into()), action_card.single_title);
+        assert!(action_card.btns.is_empty());
+
+        let link = |title: &str| Link {
+            title: title.into(),
+            url: format!("https://example.com/{}", title),
+            ..Default::default()
+        };
+        let message = Message::text("read").with_link(link("a"));
+        assert_eq!(
+            DingTalkMessage::link("a", "read", "https://example.com/a", ""),
+            (&message).into()
+        );
+        let message = message.with_link(link("b"));
+        assert_eq!(
+            DingTalkMessage::markdown(
+                "read",
+                "read\n\n[a](https://example.com/a)\n\n[b](https://example.com/b)"
+            ),
+            (&message).into()
+        );
+        let DingTalkMessage::FeedCard { feed_card } =
+            (&message.clone().with_msgtype(MessageType::FeedCard)).into()
+        else {
+            panic!("links make a feed card when asked for");
+        };
+        assert_eq!(2, feed_card.links.len());
+
+        // markdown keeps its body and title next to the links
+        let message = Message::markdown("Water", "**drink**", "drink")
+            .with_link(link("a"))
+            .with_link(link("b"));
+        assert_eq!(
+            DingTalkMessage::markdown(
+                "Water",
+                "**drink**\n\n[a](https://example.com/a)\n\n[b](https://example.com/b)"
+            ),
+            (&message).into()
+        );
+        assert_eq!(
+            DingTalkMessage::link("a", "drink", "https://example.com/a", ""),
+            (&message.clone().with_msgtype(MessageType::Link)).into()
+        );
+
+        // a type the message lacks the parts of is not used
+        let message = Message::text("drink water").with_msgtype(MessageType::FeedCard);
+        assert_eq!(DingTalkMessage::text("drink water"), (&message).into());
+    }
+}
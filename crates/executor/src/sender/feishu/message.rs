@@ -0,0 +1,313 @@
+use serde::Serialize;
+
+use crate::{Image, Mention, Message};
+
+/// A message of one of the types Feishu custom bots send.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "msg_type", rename_all = "lowercase")]
+pub enum FeishuMessage {
+    /// Mentions people with `<at user_id="...">` tags in the text.
+    Text { content: FeishuText },
+    /// Rich text of paragraphs.
+    Post { content: FeishuPostContent },
+    /// Message card.
+    Interactive { card: FeishuCard },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeishuText {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeishuPostContent {
+    pub post: FeishuPost,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeishuPost {
+    pub zh_cn: FeishuPostLocale,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeishuPostLocale {
+    pub title: String,
+    /// Paragraphs of inline elements.
+    pub content: Vec<Vec<FeishuPostElement>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "tag", rename_all = "lowercase")]
+pub enum FeishuPostElement {
+    Text {
+        text: String,
+    },
+    A {
+        text: String,
+        href: String,
+    },
+    /// `all` mentions everyone.
+    At {
+        user_id: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeishuCard {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header: Option<FeishuCardHeader>,
+    pub elements: Vec<FeishuCardElement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeishuCardHeader {
+    pub title: FeishuPlainText,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeishuPlainText {
+    pub tag: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "tag", rename_all = "lowercase")]
+pub enum FeishuCardElement {
+    /// Card markdown, mentioning people with `<at id=...></at>`.
+    Markdown {
+        content: String,
+    },
+    Action {
+        actions: Vec<FeishuCardAction>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "tag", rename_all = "lowercase")]
+pub enum FeishuCardAction {
+    Button {
+        text: FeishuPlainText,
+        url: String,
+        /// `default`, `primary` or `danger`.
+        #[serde(rename = "type")]
+        kind: String,
+    },
+}
+
+impl FeishuPlainText {
+    pub fn new(content: &str) -> FeishuPlainText {
+        FeishuPlainText {
+            tag: "plain_text".into(),
+            content: content.into(),
+        }
+    }
+}
+
+impl FeishuMessage {
+    pub fn text(text: &str) -> FeishuMessage {
+        FeishuMessage::Text {
+            content: FeishuText { text: text.into() },
+        }
+    }
+
+    pub fn post(title: &str, content: Vec<Vec<FeishuPostElement>>) -> FeishuMessage {
+        FeishuMessage::Post {
+            content: FeishuPostContent {
+                post: FeishuPost {
+                    zh_cn: FeishuPostLocale {
+                        title: title.into(),
+                        content,
+                    },
+                },
+            },
+        }
+    }
+
+    pub fn card(card: FeishuCard) -> FeishuMessage {
+        FeishuMessage::Interactive { card }
+    }
+}
+
+/// Buttons make a card, titles, markdown and links a post and anything else
+/// text. Mentioned users and everyone become `<at>` tags; Feishu bots cannot
+/// mention by mobile, so mobiles are written as plain `@13800000000`.
+impl From<&Message> for FeishuMessage {
+    fn from(message: &Message) -> Self {
+        if !message.buttons.is_empty() {
+            let mut content = message
+                .markdown
+                .clone()
+                .unwrap_or_else(|| message.text.clone());
+            for link in &message.links {
+                content.push_str(&format!("\n[{}]({})", link.title, link.url));
+            }
+            let mentions = mentions(message, |id| format!("<at id={}></at>", id));
+            if !mentions.is_empty() {
+                content.push_str(&format!("\n{}", mentions));
+            }
+            let actions = message
+                .buttons
+                .iter()
+                .enumerate()
+                .map(|(i, button)| FeishuCardAction::Button {
+                    text: FeishuPlainText::new(&button.title),
+                    url: button.url.clone(),
+                    kind: if i == 0 { "primary" } else { "default" }.into(),
+                })
+                .collect();
+            return FeishuMessage::card(FeishuCard {
+                header: message.title.as_ref().map(|title| FeishuCardHeader {
+                    title: FeishuPlainText::new(title),
+                }),
+                elements: vec![
+                    FeishuCardElement::Markdown { content },
+                    FeishuCardElement::Action { actions },
+                ],
+            });
+        }
+        if message.title.is_some() || message.markdown.is_some() || !message.links.is_empty() {
+            let mut content = message
+                .text
+                .lines()
+                .map(|line| vec![FeishuPostElement::Text { text: line.into() }])
+                .collect::<Vec<_>>();
+            let links = message
+                .links
+                .iter()
+                .map(|link| (link.title.clone(), link.url.clone()))
+                .chain(message.images.iter().filter_map(|image| match image {
+                    Image::Url(url) => Some((url.clone(), url.clone())),
+                    Image::Bytes(_) => None,
+                }));
+            for (text, href) in links {
+                content.push(vec![FeishuPostElement::A { text, href }]);
+            }
+            let mentions = message
+                .mentions
+                .iter()
+                .map(|mention| match mention {
+                    Mention::All => FeishuPostElement::At {
+                        user_id: "all".into(),
+                    },
+                    Mention::User(user) => FeishuPostElement::At {
+                        user_id: user.clone(),
+                    },
+                    Mention::Mobile(mobile) => FeishuPostElement::Text {
+                        text: format!("@{} ", mobile),
+                    },
+                })
+                .collect::<Vec<_>>();
+            if !mentions.is_empty() {
+                content.push(mentions);
+            }
+            return FeishuMessage::post(message.title.as_deref().unwrap_or_default(), content);
+        }
+        let mut text = message.plain_text();
+        let mentions = mentions(message, |id| match id {
+            "all" => "<at user_id=\"all\">所有人</at>".into(),
+            id => format!("<at user_id=\"{}\"></at>", id),
+        });
+        if !mentions.is_empty() {
+            text.push(' ');
+            text.push_str(&mentions);
+        }
+        FeishuMessage::text(&text)
+    }
+}
+
+// the mentions of the message with `tag` of the user id or `all`, mobiles as
+// plain text
+fn mentions(message: &Message, tag: impl Fn(&str) -> String) -> String {
+    message
+        .mentions
+        .iter()
+        .map(|mention| match mention {
+            Mention::All => tag("all"),
+            Mention::User(user) => tag(user),
+            Mention::Mobile(mobile) => format!("@{}", mobile),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{Button, Link};
+
+    use super::*;
+
+    #[test]
+    fn test_from_message() -> anyhow::Result<()> {
+        let message = Message::text("you are on duty")
+            .with_mention(Mention::User("ou_123".into()))
+            .with_mention(Mention::All);
+        assert_eq!(
+            json!({
+                "msg_type": "text",
+                "content": {
+                    "text": "you are on duty <at user_id=\"ou_123\"></at> \
+                             <at user_id=\"all\">所有人</at>",
+                },
+            }),
+            serde_json::to_value(FeishuMessage::from(&message))?
+        );
+
+        let message = Message::text("stay hydrated")
+            .with_title("Water")
+            .with_link(Link {
+                title: "Why".into(),
+                url: "https://example.com/why".into(),
+                ..Default::default()
+            })
+            .with_mention(Mention::User("ou_123".into()));
+        assert_eq!(
+            json!({
+                "msg_type": "post",
+                "content": {
+                    "post": {
+                        "zh_cn": {
+                            "title": "Water",
+                            "content": [
+                                [{ "tag": "text", "text": "stay hydrated" }],
+                                [{ "tag": "a", "text": "Why", "href": "https://example.com/why" }],
+                                [{ "tag": "at", "user_id": "ou_123" }],
+                            ],
+                        },
+                    },
+                },
+            }),
+            serde_json::to_value(FeishuMessage::from(&message))?
+        );
+
+        let message = Message::markdown("Water", "**round 3**", "round 3")
+            .with_button(Button {
+                title: "I drank it ✅".into(),
+                url: "https://example.com/drank".into(),
+            })
+            .with_mention(Mention::User("ou_123".into()));
+        assert_eq!(
+            json!({
+                "msg_type": "interactive",
+                "card": {
+                    "header": { "title": { "tag": "plain_text", "content": "Water" } },
+                    "elements": [
+                        { "tag": "markdown", "content": "**round 3**\n<at id=ou_123></at>" },
+                        {
+                            "tag": "action",
+                            "actions": [{
+                                "tag": "button",
+                                "text": { "tag": "plain_text", "content": "I drank it ✅" },
+                                "url": "https://example.com/drank",
+                                "type": "primary",
+                            }],
+                        },
+                    ],
+                },
+            }),
+            serde_json::to_value(FeishuMessage::from(&message))?
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex as SyncMutex, OnceLock},
+    time::Duration,
+};
+
+use tokio::{sync::Mutex, time::Instant};
+
+/// Token bucket letting `capacity` sends through at once and refilling them
+/// evenly over `period`. Sends beyond that wait for a permit in the order they
+/// came instead of being rejected by the platform.
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    // time it takes to refill a single permit
+    refill: Duration,
+    bucket: Mutex<Bucket>,
+}
+
+// limiters by key, e.g. the webhook of a robot
+static SHARED: OnceLock<SyncMutex<HashMap<String, Arc<RateLimiter>>>> = OnceLock::new();
+
+#[derive(Debug)]
+struct Bucket {
+    permits: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(capacity: u32, period: Duration) -> RateLimiter {
+        let capacity = capacity.max(1);
+        RateLimiter {
+            capacity: capacity as f64,
+            refill: period / capacity,
+            bucket: Mutex::new(Bucket {
+                permits: capacity as f64,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// The limiter registered under `key`, created with the given limit by the
+    /// first caller. Senders of the same webhook share its limit this way,
+    /// across reloads too.
+    pub fn shared(key: &str, capacity: u32, period: Duration) -> Arc<RateLimiter> {
+        let mut shared = SHARED.get_or_init(Default::default).lock().unwrap();
+        shared
+            .entry(key.into())
+            .or_insert_with(|| Arc::new(RateLimiter::new(capacity, period)))
+            .clone()
+    }
+
+    /// Wait for a permit. The lock is held while waiting, so whoever asked
+    /// first goes first.
+    pub async fn acquire(&self) {
+        let mut bucket = self.bucket.lock().await;
+        self.refill(&mut bucket);
+        if bucket.permits < 1.0 {
+            let wait = self.refill.mul_f64(1.0 - bucket.permits);
+            tokio::time::sleep(wait).await;
+            self.refill(&mut bucket);
+        }
+        bucket.permits = (bucket.permits - 1.0).max(0.0);
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at);
+        bucket.permits =
+            (bucket.permits + elapsed.as_secs_f64() / self.refill.as_secs_f64()).min(self.capacity);
+        bucket.refilled_at = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_acquire() {
+        let limiter = Arc::new(RateLimiter::new(2, Duration::from_millis(200)));
+        let started = Instant::now();
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert!(started.elapsed() < Duration::from_millis(50));
+
+        // the queued sends get a permit every 100ms
+        let queued = (0..2)
+            .map(|_| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move { limiter.acquire().await })
+            })
+            .collect::<Vec<_>>();
+        for send in queued {
+            send.await.unwrap();
+        }
+        assert!(started.elapsed() >= Duration::from_millis(190));
+    }
+}
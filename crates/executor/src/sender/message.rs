@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+
+/// A message independent of the platform it is sent to. Senders use the
+/// richest parts their platform supports and fall back to `plain_text`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Message {
+    pub title: Option<String>,
+    /// Plain text content, always set.
+    pub text: String,
+    /// Markdown version of the content, preferred over `text` where supported.
+    pub markdown: Option<String>,
+    pub mentions: Vec<Mention>,
+    pub links: Vec<Link>,
+    pub images: Vec<Image>,
+    pub buttons: Vec<Button>,
+    /// Type the sender uses where its platform has it, instead of the one it
+    /// picks from the parts of the message.
+    pub msgtype: Option<MessageType>,
+}
+
+/// Message types by their DingTalk names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MessageType {
+    Text,
+    Markdown,
+    /// Card of the first link.
+    Link,
+    /// Markdown card with the buttons.
+    ActionCard,
+    /// A card for every link, without the content.
+    FeedCard,
+}
+
+/// Someone to notify with the message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mention {
+    All,
+    Mobile(String),
+    /// Platform specific user id.
+    User(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Link {
+    pub title: String,
+    pub url: String,
+    pub description: Option<String>,
+    /// Cover picture shown with the link.
+    pub image_url: Option<String>,
+}
+
+/// An action opening `url`, e.g. "I drank it ✅".
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Button {
+    pub title: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Image {
+    Url(String),
+    /// Raw image content, e.g. a PNG file.
+    Bytes(Vec<u8>),
+}
+
+impl Message {
+    pub fn text(text: &str) -> Message {
+        Message {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Markdown message, `text` is used by platforms without markdown.
+    pub fn markdown(title: &str, markdown: &str, text: &str) -> Message {
+        Message {
+            title: Some(title.into()),
+            text: text.into(),
+            markdown: Some(markdown.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_mention(mut self, mention: Mention) -> Self {
+        self.mentions.push(mention);
+        self
+    }
+
+    pub fn with_link(mut self, link: Link) -> Self {
+        self.links.push(link);
+        self
+    }
+
+    pub fn with_image(mut self, image: Image) -> Self {
+        self.images.push(image);
+        self
+    }
+
+    pub fn with_button(mut self, button: Button) -> Self {
+        self.buttons.push(button);
+        self
+    }
+
+    pub fn with_msgtype(mut self, msgtype: MessageType) -> Self {
+        self.msgtype = Some(msgtype);
+        self
+    }
+
+    /// Title, text, links, image urls and buttons as plain text, for platforms that
+    /// only take text.
+    pub fn plain_text(&self) -> String {
+        let mut lines = vec![];
+        if let Some(title) = &self.title {
+            lines.push(title.clone());
+        }
+        lines.push(self.text.clone());
+        for link in &self.links {
+            lines.push(format!("{}: {}", link.title, link.url));
+        }
+        for image in &self.images {
+            if let Image::Url(url) = image {
+                lines.push(url.clone());
+            }
+        }
+        for button in &self.buttons {
+            lines.push(format!("{}: {}", button.title, button.url));
+        }
+        lines.join("\n")
+    }
+}
+
+/// What the platform replied to a delivered message.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeliveryReceipt {
+    pub platform: String,
+    /// Status code of the platform, e.g. DingTalk's `errcode`.
+    pub code: Option<i64>,
+    pub message: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text() {
+        let message = Message::text("drink water")
+            .with_title("Reminder")
+            .with_link(Link {
+                title: "Why".into(),
+                url: "https://example.com/water".into(),
+                ..Default::default()
+            })
+            .with_image(Image::Url("https://example.com/cup.png".into()))
+            .with_image(Image::Bytes(vec![0x89]))
+            .with_button(Button {
+                title: "Done".into(),
+                url: "https://example.com/done".into(),
+            });
+        assert_eq!(
+            "Reminder\ndrink water\nWhy: https://example.com/water\nhttps://example.com/cup.png\n\
+             Done: https://example.com/done",
+            message.plain_text()
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use std::{fmt, sync::Arc};
+
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+
+use crate::{RetryPolicy, Retryable};
+
+use super::{DeliveryReceipt, RateLimiter};
+
+/// Posts messages to the webhook of a group robot. Sends wait for the rate
+/// limiter of the webhook and transient failures are retried within a single
+/// send.
+pub(crate) struct Webhook {
+    platform: &'static str,
+    client: Client,
+    retry: RetryPolicy,
+    limiter: Arc<RateLimiter>,
+    // error codes of the platform worth another attempt
+    retryable: fn(i64) -> bool,
+}
+
+impl Webhook {
+    pub(crate) fn new(
+        platform: &'static str,
+        limiter: Arc<RateLimiter>,
+        retryable: fn(i64) -> bool,
+    ) -> Webhook {
+        Webhook {
+            platform,
+            client: Client::new(),
+            retry: RetryPolicy::new(3),
+            limiter,
+            retryable,
+        }
+    }
+
+    pub(crate) fn set_limiter(&mut self, limiter: Arc<RateLimiter>) {
+        self.limiter = limiter;
+    }
+
+    /// Post the request built for every attempt, so signatures are fresh.
+    pub(crate) async fn post<F>(&self, request: F) -> anyhow::Result<WebhookReply>
+    where
+        F: Fn(&Client) -> anyhow::Result<RequestBuilder>,
+    {
+        self.retry
+            .run(|_| async {
+                self.limiter.acquire().await;
+                let reply = request(&self.client)?
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<WebhookReply>()
+                    .await?;
+                if reply.code != 0 {
+                    return Err(WebhookError {
+                        platform: self.platform.into(),
+                        retryable: (self.retryable)(reply.code),
+                        code: reply.code,
+                        message: reply.message,
+                    }
+                    .into());
+                }
+                Ok(reply)
+            })
+            .await
+    }
+
+    pub(crate) fn receipt(&self, reply: WebhookReply) -> DeliveryReceipt {
+        DeliveryReceipt {
+            platform: self.platform.into(),
+            code: Some(reply.code),
+            message: Some(reply.message),
+        }
+    }
+}
+
+/// Reply of a robot webhook. DingTalk and WeCom answer `errcode`/`errmsg`,
+/// Feishu `code`/`msg` and older Feishu bots `StatusCode`/`StatusMessage`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "RawReply")]
+pub struct WebhookReply {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(Deserialize)]
+struct RawReply {
+    errcode: Option<i64>,
+    errmsg: Option<String>,
+    code: Option<i64>,
+    msg: Option<String>,
+    #[serde(rename = "StatusCode")]
+    status_code: Option<i64>,
+    #[serde(rename = "StatusMessage")]
+    status_message: Option<String>,
+}
+
+impl From<RawReply> for WebhookReply {
+    fn from(raw: RawReply) -> WebhookReply {
+        WebhookReply {
+            code: raw.errcode.or(raw.code).or(raw.status_code).unwrap_or(0),
+            message: raw
+                .errmsg
+                .or(raw.msg)
+                .or(raw.status_message)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// A platform rejected a message with a non-zero code in the webhook reply.
+#[derive(Debug, Clone)]
+pub struct WebhookError {
+    /// Name of the platform, e.g. `dingtalk`.
+    pub platform: String,
+    pub code: i64,
+    pub message: String,
+    retryable: bool,
+}
+
+impl WebhookError {
+    pub fn new(platform: &str, code: i64, message: &str) -> WebhookError {
+        WebhookError {
+            platform: platform.into(),
+            code,
+            message: message.into(),
+            retryable: false,
+        }
+    }
+
+    /// Whether the platform documents the code as worth another attempt.
+    pub fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+}
+
+impl Retryable for WebhookError {
+    fn is_retryable(&self) -> bool {
+        self.retryable
+    }
+}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} error {}: {}", self.platform, self.code, self.message)
+    }
+}
+
+impl std::error::Error for WebhookError {}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use axum::{routing, Json, Router};
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::sender::{dingtalk, feishu, wecom};
+
+    // webhook answering the first request with `code` and the others with 0,
+    // returns the url and the count of requests
+    async fn stub_webhook(code: i64) -> anyhow::Result<(String, Arc<AtomicUsize>)> {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let app = Router::new().route(
+            "/send",
+            routing::post(move || async move {
+                let code = match counter.fetch_add(1, Ordering::SeqCst) {
+                    0 => code,
+                    _ => 0,
+                };
+                Json(json!({ "errcode": code, "errmsg": format!("error {}", code) }))
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/send", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Ok((url, requests))
+    }
+
+    // the requests a post takes when the webhook first answers `code`
+    async fn post(classifier: fn(i64) -> bool, code: i64) -> anyhow::Result<usize> {
+        let (url, requests) = stub_webhook(code).await?;
+        let limiter = Arc::new(RateLimiter::new(10, Duration::from_secs(1)));
+        let webhook = Webhook::new("test", limiter, classifier);
+        if let Err(err) = webhook
+            .post(|client| Ok(client.post(&url).json(&Value::Null)))
+            .await
+        {
+            let err = err.downcast_ref::<WebhookError>().unwrap();
+            assert_eq!(code, err.code);
+            assert!(!err.is_retryable());
+        }
+        Ok(requests.load(Ordering::SeqCst))
+    }
+
+    // a transient code is tried again, a permanent one is not
+    async fn check(classifier: fn(i64) -> bool, transient: i64, permanent: i64) {
+        assert_eq!(2, post(classifier, transient).await.unwrap());
+        assert_eq!(1, post(classifier, permanent).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_retry_classification() {
+        // rate limited, a missing keyword
+        check(dingtalk::retryable, 660026, 310000).await;
+        // over the rate limit, an invalid key
+        check(wecom::retryable, 45009, 93000).await;
+        // over the frequency limit, a missing keyword
+        check(feishu::retryable, 11232, 19024).await;
+    }
+
+    #[test]
+    fn test_reply() -> anyhow::Result<()> {
+        let reply: WebhookReply = serde_json::from_str(r#"{"errcode":0,"errmsg":"ok"}"#)?;
+        assert_eq!((0, "ok"), (reply.code, reply.message.as_str()));
+        let reply: WebhookReply =
+            serde_json::from_str(r#"{"code":19021,"msg":"sign match fail","data":{}}"#)?;
+        assert_eq!(
+            (19021, "sign match fail"),
+            (reply.code, reply.message.as_str())
+        );
+        // Feishu may answer with both spellings at once
+        let reply: WebhookReply = serde_json::from_str(
+            r#"{"StatusCode":0,"StatusMessage":"success","code":0,"data":{},"msg":"success"}"#,
+        )?;
+        assert_eq!((0, "success"), (reply.code, reply.message.as_str()));
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+mod message;
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use reqwest::header::CONTENT_TYPE;
+
+use super::{
+    webhook::{Webhook, WebhookReply},
+    DeliveryReceipt, Message, RateLimiter, Sender,
+};
+
+pub use message::{WeComArticle, WeComImage, WeComMarkdown, WeComMessage, WeComNews, WeComText};
+
+static PLATFORM: &str = "wecom";
+// a robot takes at most 20 messages a minute
+static MESSAGES_PER_MINUTE: u32 = 20;
+// error codes worth another attempt: system busy and over the rate limit
+static RETRYABLE_ERRCODES: [i64; 2] = [-1, 45009];
+
+/// Sends messages to a WeCom (企业微信) group robot through its webhook,
+/// `https://qyapi.weixin.qq.com/cgi-bin/webhook/send?key=...`.
+pub struct WeComSender {
+    webhook: Webhook,
+    url: String,
+}
+
+impl WeComSender {
+    pub fn new(url: &str) -> WeComSender {
+        let limiter = RateLimiter::shared(url, MESSAGES_PER_MINUTE, Duration::from_secs(60));
+        WeComSender {
+            webhook: Webhook::new(PLATFORM, limiter, retryable),
+            url: url.into(),
+        }
+    }
+
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.webhook.set_limiter(limiter);
+        self
+    }
+
+    /// Send a WeCom message as is, e.g. a news card.
+    pub async fn send_message(&self, message: &WeComMessage) -> anyhow::Result<WebhookReply> {
+        self.webhook
+            .post(|client| {
+                Ok(client
+                    .post(&self.url)
+                    .header(CONTENT_TYPE, "application/json")
+                    .json(message))
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl Sender for WeComSender {
+    fn platform(&self) -> &str {
+        PLATFORM
+    }
+
+    /// Sends the images of the message after its content. Once the content is
+    /// delivered the send succeeds, a retry would repeat it, so images WeCom
+    /// rejects are only noted in the message of the receipt.
+    async fn send(&self, message: &Message) -> anyhow::Result<DeliveryReceipt> {
+        let reply = self.send_message(&message.into()).await?;
+        let mut receipt = self.webhook.receipt(reply);
+        let images = WeComMessage::images(message);
+        let mut failures = vec![];
+        for image in &images {
+            if let Err(err) = self.send_message(image).await {
+                failures.push(format!("{:#}", err));
+            }
+        }
+        if !failures.is_empty() {
+            receipt.message = Some(format!(
+                "{}; {} of {} images not sent: {}",
+                receipt.message.unwrap_or_default(),
+                failures.len(),
+                images.len(),
+                failures.join("; ")
+            ));
+        }
+        Ok(receipt)
+    }
+}
+
+pub(super) fn retryable(errcode: i64) -> bool {
+    RETRYABLE_ERRCODES.contains(&errcode)
+}
@@ -0,0 +1,265 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use md5::{Digest, Md5};
+use serde::Serialize;
+
+use crate::{Image, Mention, Message};
+
+// a news message takes at most 8 articles
+const MAX_ARTICLES: usize = 8;
+
+/// A message of one of the types WeCom robots send.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "msgtype", rename_all = "lowercase")]
+pub enum WeComMessage {
+    Text { text: WeComText },
+    Markdown { markdown: WeComMarkdown },
+    News { news: WeComNews },
+    Image { image: WeComImage },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WeComText {
+    pub content: String,
+    /// User ids to mention, `@all` for everyone.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mentioned_list: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mentioned_mobile_list: Vec<String>,
+}
+
+/// Markdown content, mentioning users with `<@userid>`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeComMarkdown {
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeComNews {
+    pub articles: Vec<WeComArticle>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeComArticle {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub picurl: Option<String>,
+}
+
+/// A JPG or PNG image of at most 2MB.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeComImage {
+    pub base64: String,
+    /// Hex md5 of the image before encoding.
+    pub md5: String,
+}
+
+impl WeComMessage {
+    pub fn text(content: &str) -> WeComMessage {
+        WeComMessage::Text {
+            text: WeComText {
+                content: content.into(),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn markdown(content: &str) -> WeComMessage {
+        WeComMessage::Markdown {
+            markdown: WeComMarkdown {
+                content: content.into(),
+            },
+        }
+    }
+
+    pub fn news(articles: Vec<WeComArticle>) -> WeComMessage {
+        WeComMessage::News {
+            news: WeComNews { articles },
+        }
+    }
+
+    pub fn image(image: &[u8]) -> WeComMessage {
+        WeComMessage::Image {
+            image: WeComImage {
+                base64: STANDARD.encode(image),
+                md5: format!("{:x}", Md5::digest(image)),
+            },
+        }
+    }
+
+    /// Image messages of the raw images of the message, which no other WeCom
+    /// type carries.
+    pub fn images(message: &Message) -> Vec<WeComMessage> {
+        message
+            .images
+            .iter()
+            .filter_map(|image| match image {
+                Image::Bytes(bytes) => Some(WeComMessage::image(bytes)),
+                Image::Url(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Markdown notifies mentioned users only, so messages mentioning mobiles or
+/// everyone are sent as text, as are mentions with links, which a news
+/// message cannot notify. Links alone make a news message.
+impl From<&Message> for WeComMessage {
+    fn from(message: &Message) -> Self {
+        let users_only = message
+            .mentions
+            .iter()
+            .all(|mention| matches!(mention, Mention::User(_)));
+        if let Some(markdown) = message.markdown.as_deref().filter(|_| users_only) {
+            return WeComMessage::markdown(&self::markdown(message, markdown));
+        }
+        if message.mentions.is_empty() && !message.links.is_empty() {
+            let mut articles = message
+                .links
+                .iter()
+                .take(MAX_ARTICLES)
+                .map(|link| WeComArticle {
+                    title: link.title.clone(),
+                    description: link.description.clone(),
+                    url: link.url.clone(),
+                    picurl: link.image_url.clone(),
+                })
+                .collect::<Vec<_>>();
+            if articles[0].description.is_none() && !message.text.is_empty() {
+                articles[0].description = Some(message.text.clone());
+            }
+            return WeComMessage::news(articles);
+        }
+        let mut text = WeComText {
+            content: message.plain_text(),
+            ..Default::default()
+        };
+        for mention in &message.mentions {
+            match mention {
+                Mention::All => text.mentioned_list.push("@all".into()),
+                Mention::User(user) => text.mentioned_list.push(user.clone()),
+                Mention::Mobile(mobile) => text.mentioned_mobile_list.push(mobile.clone()),
+            }
+        }
+        WeComMessage::Text { text }
+    }
+}
+
+// markdown with the title as heading, links, image urls and buttons as links
+// and the mentioned users at the end
+fn markdown(message: &Message, markdown: &str) -> String {
+    let mut lines = vec![];
+    if let Some(title) = &message.title {
+        lines.push(format!("# {}", title));
+    }
+    lines.push(markdown.into());
+    for link in &message.links {
+        lines.push(format!("[{}]({})", link.title, link.url));
+    }
+    for image in &message.images {
+        if let Image::Url(url) = image {
+            lines.push(format!("[{}]({})", url, url));
+        }
+    }
+    for button in &message.buttons {
+        lines.push(format!("[{}]({})", button.title, button.url));
+    }
+    let users = message
+        .mentions
+        .iter()
+        .filter_map(|mention| match mention {
+            Mention::User(user) => Some(format!("<@{}>", user)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if !users.is_empty() {
+        lines.push(users.join(" "));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{Button, Link};
+
+    use super::*;
+
+    #[test]
+    fn test_from_message() -> anyhow::Result<()> {
+        let message = Message::text("you are on duty")
+            .with_mention(Mention::User("wangqing".into()))
+            .with_mention(Mention::Mobile("13800000000".into()))
+            .with_mention(Mention::All);
+        assert_eq!(
+            json!({
+                "msgtype": "text",
+                "text": {
+                    "content": "you are on duty",
+                    "mentioned_list": ["wangqing", "@all"],
+                    "mentioned_mobile_list": ["13800000000"],
+                },
+            }),
+            serde_json::to_value(WeComMessage::from(&message))?
+        );
+
+        let message = Message::markdown("Water", "**round 3**", "round 3")
+            .with_button(Button {
+                title: "I drank it ✅".into(),
+                url: "https://example.com/drank".into(),
+            })
+            .with_mention(Mention::User("wangqing".into()));
+        assert_eq!(
+            WeComMessage::markdown(
+                "# Water\n**round 3**\n[I drank it ✅](https://example.com/drank)\n<@wangqing>"
+            ),
+            (&message).into()
+        );
+        // only text notifies mobiles
+        let message = message.with_mention(Mention::Mobile("13800000000".into()));
+        assert!(matches!(
+            WeComMessage::from(&message),
+            WeComMessage::Text { .. }
+        ));
+
+        let message = Message::text("why").with_link(Link {
+            title: "Drink water".into(),
+            url: "https://example.com/water".into(),
+            image_url: Some("https://example.com/cup.png".into()),
+            ..Default::default()
+        });
+        assert_eq!(
+            json!({
+                "msgtype": "news",
+                "news": {
+                    "articles": [{
+                        "title": "Drink water",
+                        "description": "why",
+                        "url": "https://example.com/water",
+                        "picurl": "https://example.com/cup.png",
+                    }],
+                },
+            }),
+            serde_json::to_value(WeComMessage::from(&message))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_image() -> anyhow::Result<()> {
+        let message = Message::text("cup").with_image(Image::Bytes(b"hello".to_vec()));
+        assert_eq!(
+            vec![WeComMessage::Image {
+                image: WeComImage {
+                    base64: "aGVsbG8=".into(),
+                    md5: "5d41402abc4b2a76b9719d911017c592".into(),
+                },
+            }],
+            WeComMessage::images(&message)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,9 @@
+mod client;
+mod protocol;
+mod server;
+mod shutdown;
+
+pub use client::ExecutorClient;
+pub use protocol::{ExecuteRequest, ExecuteResponse, ExecuteStatus, RemoteError};
+pub use server::{router, serve};
+pub use shutdown::shutdown_signal;
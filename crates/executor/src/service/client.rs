@@ -0,0 +1,49 @@
+use reqwest::{Client, StatusCode};
+
+use super::protocol::{ExecuteRequest, ExecuteResponse, RemoteError};
+
+/// Calls a remote executor service.
+pub struct ExecutorClient {
+    client: Client,
+    url: String,
+}
+
+impl ExecutorClient {
+    pub fn new(base_url: &str) -> ExecutorClient {
+        ExecutorClient {
+            client: Client::new(),
+            url: base_url.trim_end_matches('/').into(),
+        }
+    }
+
+    pub async fn execute(&self, request: &ExecuteRequest) -> anyhow::Result<ExecuteResponse> {
+        let reply = self
+            .client
+            .post(format!("{}/v1/execute", self.url))
+            .json(request)
+            .send()
+            .await?;
+        match reply.status() {
+            StatusCode::OK | StatusCode::NOT_FOUND => Ok(reply.json().await?),
+            status => {
+                let message = format!(
+                    "executor service replied {}: {}",
+                    status,
+                    reply.text().await.unwrap_or_default()
+                );
+                // an overloaded or failing service may answer the next attempt
+                let retryable = status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS;
+                Err(RemoteError::new(&message).with_retryable(retryable).into())
+            }
+        }
+    }
+
+    pub async fn health(&self) -> anyhow::Result<()> {
+        self.client
+            .get(format!("{}/health", self.url))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
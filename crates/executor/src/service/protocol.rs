@@ -0,0 +1,94 @@
+use std::fmt;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{ExecutionOutcome, Retryable};
+
+/// Body of `POST /v1/execute`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecuteRequest {
+    pub task_id: i32,
+    pub event_id: i32,
+    /// Identifies the occurrence, a repeated request with the same key returns
+    /// the result of the execution that succeeded or is still running instead
+    /// of executing again.
+    pub occurrence_key: String,
+    /// When the occurrence was due, the time of the request when missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_at: Option<DateTime<Local>>,
+    /// 1-based attempt of the run.
+    #[serde(default = "first_attempt")]
+    pub attempt: u32,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
+fn first_attempt() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteStatus {
+    Succeeded,
+    Failed,
+    NotFound,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecuteResponse {
+    pub status: ExecuteStatus,
+    pub occurrence_key: String,
+    /// What was delivered, or what a failed execution got to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<ExecutionOutcome>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether another attempt of a failed execution may succeed, e.g. after
+    /// the platform throttled it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub retryable: bool,
+}
+
+impl ExecuteResponse {
+    pub fn is_succeeded(&self) -> bool {
+        self.status == ExecuteStatus::Succeeded
+    }
+}
+
+/// An execution the executor service did not succeed in, or a reply of the
+/// service in error.
+#[derive(Debug, Clone)]
+pub struct RemoteError {
+    pub message: String,
+    retryable: bool,
+}
+
+impl RemoteError {
+    pub fn new(message: &str) -> RemoteError {
+        RemoteError {
+            message: message.into(),
+            retryable: false,
+        }
+    }
+
+    pub fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+}
+
+impl Retryable for RemoteError {
+    fn is_retryable(&self) -> bool {
+        self.retryable
+    }
+}
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for RemoteError {}
@@ -0,0 +1,221 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use axum::{extract::State, http::StatusCode, routing, Json, Router};
+use chrono::Local;
+use serde::Serialize;
+use tokio::{net::TcpListener, sync::watch};
+
+use crate::{
+    is_retryable, ExecutionContext, ExecutionOutcome, ExecutorId, ExecutorManager,
+    ExecutorMetadata, ExecutorNotFound,
+};
+
+use super::protocol::{ExecuteRequest, ExecuteResponse, ExecuteStatus};
+
+// successful results kept to answer repeated requests for the same occurrence
+const RECENT_CAPACITY: usize = 1024;
+
+#[derive(Clone)]
+struct AppState {
+    manager: Arc<ExecutorManager>,
+    recent: Arc<Mutex<Recent>>,
+}
+
+type Pending = watch::Receiver<Option<ExecuteResponse>>;
+
+enum Slot {
+    Running(Pending),
+    Succeeded(ExecuteResponse),
+}
+
+#[derive(Default)]
+struct Recent {
+    // succeeded keys, oldest first
+    keys: VecDeque<String>,
+    slots: HashMap<String, Slot>,
+}
+
+enum Reserve {
+    Succeeded(ExecuteResponse),
+    Running(Pending),
+    Acquired(Reservation),
+}
+
+impl Recent {
+    /// The response of a succeeded occurrence, the pending response of one
+    /// executing, or the right to execute it.
+    fn reserve(recent: &Arc<Mutex<Recent>>, key: &str) -> Reserve {
+        let mut guard = recent.lock().unwrap();
+        match guard.slots.get(key) {
+            Some(Slot::Succeeded(response)) => Reserve::Succeeded(response.clone()),
+            Some(Slot::Running(pending)) => Reserve::Running(pending.clone()),
+            None => {
+                let (tx, rx) = watch::channel(None);
+                guard.slots.insert(key.into(), Slot::Running(rx));
+                Reserve::Acquired(Reservation {
+                    recent: recent.clone(),
+                    key: key.into(),
+                    tx: Some(tx),
+                })
+            }
+        }
+    }
+
+    fn succeeded(&mut self, response: ExecuteResponse) {
+        if self.keys.len() >= RECENT_CAPACITY {
+            if let Some(key) = self.keys.pop_front() {
+                self.slots.remove(&key);
+            }
+        }
+        self.keys.push_back(response.occurrence_key.clone());
+        self.slots
+            .insert(response.occurrence_key.clone(), Slot::Succeeded(response));
+    }
+}
+
+// an occurrence being executed, released when dropped unfinished so a retry
+// can take over from a cancelled request
+struct Reservation {
+    recent: Arc<Mutex<Recent>>,
+    key: String,
+    tx: Option<watch::Sender<Option<ExecuteResponse>>>,
+}
+
+impl Reservation {
+    /// Answer the requests waiting for the occurrence. Only a success is kept,
+    /// failures are executed again by the next attempt.
+    fn finish(mut self, response: &ExecuteResponse) {
+        let mut recent = self.recent.lock().unwrap();
+        recent.slots.remove(&self.key);
+        if response.is_succeeded() {
+            recent.succeeded(response.clone());
+        }
+        if let Some(tx) = self.tx.take() {
+            tx.send_replace(Some(response.clone()));
+        }
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        if self.tx.is_some() {
+            self.recent.lock().unwrap().slots.remove(&self.key);
+        }
+    }
+}
+
+pub fn router(manager: Arc<ExecutorManager>) -> Router {
+    Router::new()
+        .route("/health", routing::get(|| async { "ok" }))
+        .route("/v1/executors", routing::get(executors))
+        .route("/v1/execute", routing::post(execute))
+        .with_state(AppState {
+            manager,
+            recent: Default::default(),
+        })
+}
+
+/// Serve execution requests until `shutdown` resolves, requests in flight are
+/// finished before returning.
+pub async fn serve<F>(
+    listener: TcpListener,
+    manager: Arc<ExecutorManager>,
+    shutdown: F,
+) -> anyhow::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    axum::serve(listener, router(manager))
+        .with_graceful_shutdown(shutdown)
+        .await?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ExecutorEntry {
+    id: ExecutorId,
+    #[serde(flatten)]
+    metadata: ExecutorMetadata,
+}
+
+async fn executors(State(state): State<AppState>) -> Json<Vec<ExecutorEntry>> {
+    let executors = state.manager.list();
+    Json(
+        executors
+            .into_iter()
+            .map(|(id, metadata)| ExecutorEntry { id, metadata })
+            .collect(),
+    )
+}
+
+async fn execute(
+    State(state): State<AppState>,
+    Json(request): Json<ExecuteRequest>,
+) -> (StatusCode, Json<ExecuteResponse>) {
+    let reservation = loop {
+        let mut pending = match Recent::reserve(&state.recent, &request.occurrence_key) {
+            Reserve::Succeeded(response) => return reply(response),
+            Reserve::Running(pending) => pending,
+            Reserve::Acquired(reservation) => break reservation,
+        };
+        // the same occurrence is executing, share its result unless that
+        // request is cancelled first
+        let shared = match pending.wait_for(Option::is_some).await {
+            Ok(response) => response.clone(),
+            Err(_) => None,
+        };
+        if let Some(response) = shared {
+            return reply(response);
+        }
+    };
+    let ctx = ExecutionContext::new(
+        request.task_id,
+        request.scheduled_at.unwrap_or_else(Local::now),
+    )
+    .with_attempt(request.attempt)
+    .with_payload(request.payload);
+    let response = match state.manager.execute(request.event_id, &ctx).await {
+        Ok(outcome) => ExecuteResponse {
+            status: ExecuteStatus::Succeeded,
+            occurrence_key: request.occurrence_key,
+            outcome: Some(outcome),
+            error: None,
+            retryable: false,
+        },
+        Err(err) if err.is::<ExecutorNotFound>() => ExecuteResponse {
+            status: ExecuteStatus::NotFound,
+            occurrence_key: request.occurrence_key,
+            outcome: None,
+            error: Some(format!(
+                "no executor registered for event id {}",
+                request.event_id
+            )),
+            retryable: false,
+        },
+        Err(err) => ExecuteResponse {
+            status: ExecuteStatus::Failed,
+            occurrence_key: request.occurrence_key,
+            outcome: ExecutionOutcome::of_error(&err).cloned(),
+            error: Some(format!("{:#}", err)),
+            retryable: is_retryable(&err),
+        },
+    };
+    println!(
+        "Task {} occurrence {}: {:?}",
+        request.task_id, response.occurrence_key, response.status
+    );
+    reservation.finish(&response);
+    reply(response)
+}
+
+fn reply(response: ExecuteResponse) -> (StatusCode, Json<ExecuteResponse>) {
+    let status = match response.status {
+        ExecuteStatus::NotFound => StatusCode::NOT_FOUND,
+        _ => StatusCode::OK,
+    };
+    (status, Json(response))
+}
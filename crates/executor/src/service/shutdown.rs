@@ -0,0 +1,36 @@
+use std::future::Future;
+
+use tokio::signal;
+
+/// Listen for SIGINT and SIGTERM, the future resolves on the first of them.
+/// Fails when the signals cannot be listened for, so a service does not run
+/// without a way to be drained.
+#[cfg(unix)]
+pub fn shutdown_signal() -> anyhow::Result<impl Future<Output = ()>> {
+    use anyhow::Context;
+    use signal::unix::SignalKind;
+
+    let mut sigint =
+        signal::unix::signal(SignalKind::interrupt()).context("failed to listen for SIGINT")?;
+    let mut sigterm =
+        signal::unix::signal(SignalKind::terminate()).context("failed to listen for SIGTERM")?;
+    Ok(async move {
+        tokio::select! {
+            _ = sigint.recv() => println!("Received SIGINT, shutting down"),
+            _ = sigterm.recv() => println!("Received SIGTERM, shutting down"),
+        }
+    })
+}
+
+/// Listen for Ctrl-C, the future resolves once it is pressed. Fails when
+/// the console handler cannot be installed.
+#[cfg(not(unix))]
+pub fn shutdown_signal() -> anyhow::Result<impl Future<Output = ()>> {
+    use anyhow::Context;
+
+    let mut ctrl_c = signal::windows::ctrl_c().context("failed to listen for Ctrl-C")?;
+    Ok(async move {
+        ctrl_c.recv().await;
+        println!("Received Ctrl-C, shutting down");
+    })
+}
@@ -0,0 +1,32 @@
+mod memory;
+mod mysql;
+
+use async_trait::async_trait;
+
+pub use memory::MemoryStateStore;
+pub use mysql::MySqlStateStore;
+
+/// Values executors keep across executions and instances, e.g. the round
+/// counter of the WaterBot.
+///
+/// Every value belongs to a period, such as the day it was counted in. A value
+/// stored for another period reads as absent, so moving on to a new period
+/// resets it.
+#[async_trait]
+pub trait StateStore: Send + Sync {
+    async fn get(&self, key: &str, period: &str) -> anyhow::Result<Option<i64>>;
+
+    /// Atomically add `delta` and return the new value, starting from 0 in a
+    /// new period.
+    async fn incr(&self, key: &str, period: &str, delta: i64) -> anyhow::Result<i64>;
+
+    /// Store `value` only if the current value is `expected`, `None` meaning
+    /// absent. Returns whether it was stored.
+    async fn compare_and_set(
+        &self,
+        key: &str,
+        period: &str,
+        expected: Option<i64>,
+        value: i64,
+    ) -> anyhow::Result<bool>;
+}
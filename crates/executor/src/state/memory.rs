@@ -0,0 +1,94 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use async_trait::async_trait;
+
+use super::StateStore;
+
+/// Keeps the state in the process, it is lost on restart and not shared
+/// between instances.
+#[derive(Debug, Default)]
+pub struct MemoryStateStore {
+    values: Mutex<HashMap<String, (String, i64)>>,
+}
+
+impl MemoryStateStore {
+    pub fn new() -> MemoryStateStore {
+        MemoryStateStore::default()
+    }
+}
+
+#[async_trait]
+impl StateStore for MemoryStateStore {
+    async fn get(&self, key: &str, period: &str) -> anyhow::Result<Option<i64>> {
+        let values = self.values.lock().unwrap();
+        Ok(values
+            .get(key)
+            .filter(|(stored, _)| stored == period)
+            .map(|(_, value)| *value))
+    }
+
+    async fn incr(&self, key: &str, period: &str, delta: i64) -> anyhow::Result<i64> {
+        let mut values = self.values.lock().unwrap();
+        let entry = values
+            .entry(key.into())
+            .or_insert_with(|| (period.into(), 0));
+        if entry.0 != period {
+            *entry = (period.into(), 0);
+        }
+        entry.1 += delta;
+        Ok(entry.1)
+    }
+
+    async fn compare_and_set(
+        &self,
+        key: &str,
+        period: &str,
+        expected: Option<i64>,
+        value: i64,
+    ) -> anyhow::Result<bool> {
+        let mut values = self.values.lock().unwrap();
+        let current = values
+            .get(key)
+            .filter(|(stored, _)| stored == period)
+            .map(|(_, value)| *value);
+        if current != expected {
+            return Ok(false);
+        }
+        values.insert(key.into(), (period.into(), value));
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_state_store() -> anyhow::Result<()> {
+        let store = MemoryStateStore::new();
+        assert_eq!(None, store.get("round", "2024-09-02").await?);
+        assert_eq!(1, store.incr("round", "2024-09-02", 1).await?);
+        assert_eq!(3, store.incr("round", "2024-09-02", 2).await?);
+        assert_eq!(Some(3), store.get("round", "2024-09-02").await?);
+
+        // a new period starts over
+        assert_eq!(None, store.get("round", "2024-09-03").await?);
+        assert_eq!(1, store.incr("round", "2024-09-03", 1).await?);
+        assert_eq!(None, store.get("round", "2024-09-02").await?);
+
+        assert!(
+            !store
+                .compare_and_set("round", "2024-09-03", Some(3), 0)
+                .await?
+        );
+        assert!(
+            store
+                .compare_and_set("round", "2024-09-03", Some(1), 0)
+                .await?
+        );
+        assert_eq!(Some(0), store.get("round", "2024-09-03").await?);
+        assert!(store.compare_and_set("other", "", None, 7).await?);
+        assert!(!store.compare_and_set("other", "", None, 8).await?);
+        Ok(())
+    }
+}
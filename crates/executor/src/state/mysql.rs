@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use sqlx::MySqlPool;
+
+use super::StateStore;
+
+/// Keeps the state in the `executor_state` table, shared by every executor
+/// instance using the database.
+pub struct MySqlStateStore {
+    pool: MySqlPool,
+}
+
+impl MySqlStateStore {
+    pub fn new(pool: MySqlPool) -> MySqlStateStore {
+        MySqlStateStore { pool }
+    }
+
+    pub async fn connect(url: &str) -> anyhow::Result<MySqlStateStore> {
+        Ok(MySqlStateStore::new(MySqlPool::connect(url).await?))
+    }
+}
+
+#[async_trait]
+impl StateStore for MySqlStateStore {
+    async fn get(&self, key: &str, period: &str) -> anyhow::Result<Option<i64>> {
+        Ok(sqlx::query_scalar::<_, i64>(
+            r#"
+SELECT `value`
+FROM `executor_state`
+WHERE `key` = ? AND `period` = ?"#,
+        )
+        .bind(key)
+        .bind(period)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    async fn incr(&self, key: &str, period: &str, delta: i64) -> anyhow::Result<i64> {
+        let mut tx = self.pool.begin().await?;
+        // assignments run in order, `value` still compares the old period
+        sqlx::query(
+            r#"
+INSERT INTO `executor_state` (`key`, `period`, `value`)
+VALUES (?, ?, ?)
+ON DUPLICATE KEY UPDATE
+    `value` = IF(`period` = VALUES(`period`), `value` + VALUES(`value`), VALUES(`value`)),
+    `period` = VALUES(`period`)"#,
+        )
+        .bind(key)
+        .bind(period)
+        .bind(delta)
+        .execute(&mut *tx)
+        .await?;
+        // the row stays locked by the upsert until the commit
+        let value =
+            sqlx::query_scalar::<_, i64>("SELECT `value` FROM `executor_state` WHERE `key` = ?")
+                .bind(key)
+                .fetch_one(&mut *tx)
+                .await?;
+        tx.commit().await?;
+        Ok(value)
+    }
+
+    async fn compare_and_set(
+        &self,
+        key: &str,
+        period: &str,
+        expected: Option<i64>,
+        value: i64,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let stored = sqlx::query_as::<_, (String, i64)>(
+            "SELECT `period`, `value` FROM `executor_state` WHERE `key` = ? FOR UPDATE",
+        )
+        .bind(key)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let current = stored
+            .as_ref()
+            .filter(|(stored, _)| stored == period)
+            .map(|(_, value)| *value);
+        if current != expected {
+            tx.commit().await?;
+            return Ok(false);
+        }
+        let stored = match stored {
+            // a concurrent insert wins, the loser sees no affected row
+            None => sqlx::query(
+                "INSERT IGNORE INTO `executor_state` (`key`, `period`, `value`) VALUES (?, ?, ?)",
+            )
+            .bind(key)
+            .bind(period)
+            .bind(value),
+            Some(_) => {
+                sqlx::query("UPDATE `executor_state` SET `period` = ?, `value` = ? WHERE `key` = ?")
+                    .bind(period)
+                    .bind(value)
+                    .bind(key)
+            }
+        }
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            == 1;
+        tx.commit().await?;
+        Ok(stored)
+    }
+}
@@ -0,0 +1,82 @@
+// each test binary uses a part of the helpers
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{extract::Query, routing, Json, Router};
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+
+pub type Received = Arc<Mutex<Vec<Value>>>;
+
+/// A request received by [`stub_webhook`].
+pub struct Request {
+    /// 0-based position of the request.
+    pub index: usize,
+    pub query: HashMap<String, String>,
+    pub body: Value,
+}
+
+/// How a platform shapes its replies.
+#[derive(Debug, Clone, Copy)]
+pub enum Shape {
+    /// `errcode`/`errmsg` of DingTalk and WeCom.
+    ErrCode,
+    /// `code`/`msg` of Feishu.
+    Code,
+}
+
+impl Shape {
+    pub fn reply(self, code: i64, message: &str) -> Value {
+        match self {
+            Shape::ErrCode => json!({ "errcode": code, "errmsg": message }),
+            Shape::Code => json!({ "code": code, "msg": message, "data": {} }),
+        }
+    }
+}
+
+/// Robot webhook stand-in at `route`, a path with an optional query, answering
+/// every request with `reply`; returns the url and the bodies received.
+pub async fn stub_webhook<F>(route: &str, reply: F) -> anyhow::Result<(String, Received)>
+where
+    F: Fn(&Request) -> Value + Send + Sync + 'static,
+{
+    let received = Received::default();
+    let bodies = received.clone();
+    let reply = Arc::new(reply);
+    let (path, _) = route.split_once('?').unwrap_or((route, ""));
+    let app = Router::new().route(
+        path,
+        routing::post(
+            move |Query(query): Query<HashMap<String, String>>, Json(body): Json<Value>| async move {
+                let index = {
+                    let mut bodies = bodies.lock().unwrap();
+                    bodies.push(body.clone());
+                    bodies.len() - 1
+                };
+                Json(reply(&Request { index, query, body }))
+            },
+        ),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}{}", listener.local_addr()?, route);
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    Ok((url, received))
+}
+
+/// Stand-in answering the requests with the given codes in turn, 0 once they
+/// run out.
+pub async fn stub_codes(
+    route: &str,
+    shape: Shape,
+    codes: Vec<i64>,
+) -> anyhow::Result<(String, Received)> {
+    stub_webhook(route, move |request| {
+        let code = codes.get(request.index).copied().unwrap_or(0);
+        shape.reply(code, &format!("error {}", code))
+    })
+    .await
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Local;
+use common::{stub_codes, stub_webhook, Received, Shape};
+use executor::{DingTalkErrorKind, DingTalkSender, Message, RateLimiter, Sender, WebhookError};
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+
+static SECRET: &str = "SEC7f3a0c1e9b2d4";
+static ROUTE: &str = "/robot/send?access_token=token";
+
+// DingTalk robot stand-in with the "加签" setting, checking the signature the
+// way the documentation describes it
+async fn stub_signed_dingtalk() -> anyhow::Result<String> {
+    let (url, _) = stub_webhook(ROUTE, |request| {
+        verify(&request.query).unwrap_or_else(|errmsg| Shape::ErrCode.reply(310000, &errmsg))
+    })
+    .await?;
+    Ok(url)
+}
+
+// robot answering the requests with the given error codes in turn, 0 once they
+// run out
+async fn stub_dingtalk(errcodes: Vec<i64>) -> anyhow::Result<(String, Received)> {
+    stub_codes(ROUTE, Shape::ErrCode, errcodes).await
+}
+
+fn verify(query: &HashMap<String, String>) -> Result<Value, String> {
+    let timestamp = query.get("timestamp").ok_or("missing timestamp")?;
+    let sign = query.get("sign").ok_or("missing sign")?;
+    let age =
+        Local::now().timestamp_millis() - timestamp.parse::<i64>().map_err(|e| e.to_string())?;
+    if !(0..3_600_000).contains(&age) {
+        return Err("timestamp expired".into());
+    }
+    let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+    mac.update(format!("{}\n{}", timestamp, SECRET).as_bytes());
+    let expected = mac.finalize().into_bytes();
+    if STANDARD.decode(sign).map_err(|e| e.to_string())? != expected.as_slice() {
+        return Err("sign not match".into());
+    }
+    Ok(Shape::ErrCode.reply(0, "ok"))
+}
+
+#[tokio::test]
+async fn test_signed_webhook() -> anyhow::Result<()> {
+    let url = stub_signed_dingtalk().await?;
+    let message = Message::text("drink water");
+
+    let receipt = DingTalkSender::new(&url)
+        .with_secret(SECRET)
+        .send(&message)
+        .await?;
+    assert_eq!(Some(0), receipt.code);
+
+    let err = DingTalkSender::new(&url)
+        .with_secret("SECwrong")
+        .send(&message)
+        .await
+        .unwrap_err();
+    let err = err.downcast_ref::<WebhookError>().unwrap();
+    assert_eq!(
+        ("dingtalk", 310000, "sign not match"),
+        (err.platform.as_str(), err.code, err.message.as_str())
+    );
+    assert_eq!(DingTalkErrorKind::Security, DingTalkErrorKind::of(err.code));
+
+    let err = DingTalkSender::new(&url).send(&message).await.unwrap_err();
+    assert_eq!(
+        "missing timestamp",
+        err.downcast_ref::<WebhookError>().unwrap().message
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rate_limit() -> anyhow::Result<()> {
+    let (url, requests) = stub_dingtalk(vec![]).await?;
+    let limiter = Arc::new(RateLimiter::new(2, Duration::from_millis(400)));
+    let sender = Arc::new(DingTalkSender::new(&url).with_rate_limiter(limiter));
+    let started = Instant::now();
+    let sends = (0..4)
+        .map(|_| {
+            let sender = sender.clone();
+            tokio::spawn(async move { sender.send(&Message::text("drink water")).await })
+        })
+        .collect::<Vec<_>>();
+    for send in sends {
+        send.await??;
+    }
+    // two go out at once, the other two are queued rather than lost
+    assert_eq!(4, requests.lock().unwrap().len());
+    assert!(started.elapsed() >= Duration::from_millis(380));
+    Ok(())
+}
@@ -1,15 +1,17 @@
 use std::env;
 
-use executor::{new_executor_manager, WATERBOT_ID};
+use chrono::Local;
+use executor::{new_executor_manager, ExecutionContext, WATERBOT_ID};
 
-#[test]
-fn main() -> anyhow::Result<()> {
+#[tokio::test]
+async fn main() -> anyhow::Result<()> {
     dotenv::dotenv().ok();
     let dingtalk_url = env::var("DINGTALK_URL")?;
     println!("DingTalk Url: {}", dingtalk_url);
     let executor = new_executor_manager(&dingtalk_url);
+    let ctx = ExecutionContext::new(0, Local::now());
     for _ in 0..1 {
-        executor.execute(WATERBOT_ID)?;
+        executor.execute(WATERBOT_ID, &ctx).await?;
     }
     Ok(())
 }
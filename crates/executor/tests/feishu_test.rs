@@ -0,0 +1,72 @@
+mod common;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Local;
+use common::{stub_webhook, Received, Shape};
+use executor::{FeishuSender, Mention, Message, Sender, WebhookError};
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+
+static SECRET: &str = "qJ8fT2dVx0mN";
+
+// Feishu bot stand-in with the "签名校验" setting, answering the first
+// requests with the given codes and checking the signature of the others the
+// way the documentation describes it
+async fn stub_feishu(codes: Vec<i64>) -> anyhow::Result<(String, Received)> {
+    stub_webhook("/open-apis/bot/v2/hook/hook", move |request| {
+        match codes.get(request.index) {
+            Some(code) => Shape::Code.reply(*code, &format!("error {}", code)),
+            None => verify(&request.body).unwrap_or_else(|msg| Shape::Code.reply(19021, &msg)),
+        }
+    })
+    .await
+}
+
+fn verify(body: &Value) -> Result<Value, String> {
+    let timestamp = body["timestamp"].as_str().ok_or("missing timestamp")?;
+    let sign = body["sign"].as_str().ok_or("missing sign")?;
+    let age = Local::now().timestamp() - timestamp.parse::<i64>().map_err(|e| e.to_string())?;
+    if !(-60..3600).contains(&age) {
+        return Err("timestamp is not within one hour".into());
+    }
+    let key = format!("{}\n{}", timestamp, SECRET);
+    let expected = Hmac::<Sha256>::new_from_slice(key.as_bytes())
+        .unwrap()
+        .finalize()
+        .into_bytes();
+    if STANDARD.decode(sign).map_err(|e| e.to_string())? != expected.as_slice() {
+        return Err("sign match fail".into());
+    }
+    if body["msg_type"] != "text" || !body["content"]["text"].is_string() {
+        return Err("params error".into());
+    }
+    Ok(Shape::Code.reply(0, "success"))
+}
+
+#[tokio::test]
+async fn test_signed_webhook() -> anyhow::Result<()> {
+    let (url, _) = stub_feishu(vec![]).await?;
+    let message = Message::text("drink water").with_mention(Mention::User("ou_123".into()));
+
+    let receipt = FeishuSender::new(&url)
+        .with_secret(SECRET)
+        .send(&message)
+        .await?;
+    assert_eq!(
+        ("feishu", Some(0)),
+        (receipt.platform.as_str(), receipt.code)
+    );
+
+    let err = FeishuSender::new(&url)
+        .with_secret("wrong")
+        .send(&message)
+        .await
+        .unwrap_err();
+    let err = err.downcast_ref::<WebhookError>().unwrap();
+    assert_eq!(
+        ("feishu", 19021, "sign match fail"),
+        (err.platform.as_str(), err.code, err.message.as_str())
+    );
+    Ok(())
+}
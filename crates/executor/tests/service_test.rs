@@ -0,0 +1,140 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{stub_codes, Received, Shape};
+use executor::{
+    new_executor_manager, serve, ExecuteRequest, ExecuteStatus, ExecutorClient, WATERBOT_ID,
+};
+use serde_json::Value;
+use tokio::net::TcpListener;
+
+// DingTalk robot stand-in recording the bodies it receives
+async fn stub_dingtalk() -> anyhow::Result<(String, Received)> {
+    stub_codes("/robot/send", Shape::ErrCode, vec![]).await
+}
+
+async fn start_service(dingtalk_url: &str) -> anyhow::Result<ExecutorClient> {
+    let manager = Arc::new(new_executor_manager(dingtalk_url));
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}", listener.local_addr()?);
+    tokio::spawn(serve(listener, manager, std::future::pending()));
+    Ok(ExecutorClient::new(&url))
+}
+
+fn request(event_id: i32, occurrence_key: &str) -> ExecuteRequest {
+    ExecuteRequest {
+        task_id: 1,
+        event_id,
+        occurrence_key: occurrence_key.into(),
+        scheduled_at: None,
+        attempt: 1,
+        payload: Value::Null,
+    }
+}
+
+#[tokio::test]
+async fn test_execute_remotely() -> anyhow::Result<()> {
+    let (dingtalk_url, received) = stub_dingtalk().await?;
+    let client = start_service(&dingtalk_url).await?;
+    client.health().await?;
+
+    let response = client
+        .execute(&request(WATERBOT_ID as i32, "1:1:202409021040"))
+        .await?;
+    assert_eq!(ExecuteStatus::Succeeded, response.status);
+    assert_eq!("1:1:202409021040", response.occurrence_key);
+    let outcome = response.outcome.unwrap();
+    assert_eq!("dingtalk", outcome.sender);
+    assert_eq!(Some(0), outcome.response_code);
+    assert!(outcome.content.contains("第1轮"));
+    {
+        let received = received.lock().unwrap();
+        assert_eq!(1, received.len());
+        assert_eq!("text", received[0]["msgtype"]);
+        assert!(received[0]["text"]["content"]
+            .as_str()
+            .unwrap()
+            .contains("第1轮"));
+    }
+
+    // a retried occurrence is answered without sending again
+    let response = client
+        .execute(&request(WATERBOT_ID as i32, "1:1:202409021040"))
+        .await?;
+    assert!(response.is_succeeded());
+    assert_eq!(1, received.lock().unwrap().len());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_execute_concurrently() -> anyhow::Result<()> {
+    let (dingtalk_url, received) = stub_dingtalk().await?;
+    let client = Arc::new(start_service(&dingtalk_url).await?);
+
+    // duplicates arriving together share the result of a single execution
+    let requests = (0..4)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                client
+                    .execute(&request(WATERBOT_ID as i32, "1:1:202409021040"))
+                    .await
+            })
+        })
+        .collect::<Vec<_>>();
+    for request in requests {
+        assert!(request.await??.is_succeeded());
+    }
+    assert_eq!(1, received.lock().unwrap().len());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_execute_failed_again() -> anyhow::Result<()> {
+    let (dingtalk_url, received) = stub_codes("/robot/send", Shape::ErrCode, vec![310000]).await?;
+    let client = start_service(&dingtalk_url).await?;
+
+    let response = client
+        .execute(&request(WATERBOT_ID as i32, "1:1:202409021040"))
+        .await?;
+    assert_eq!(ExecuteStatus::Failed, response.status);
+    assert!(!response.retryable);
+    assert_eq!(Some(310000), response.outcome.unwrap().response_code);
+
+    // failures are not kept, the retry sends again
+    let response = client
+        .execute(&request(WATERBOT_ID as i32, "1:1:202409021040"))
+        .await?;
+    assert!(response.is_succeeded());
+    assert_eq!(2, received.lock().unwrap().len());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_execute_throttled() -> anyhow::Result<()> {
+    let (dingtalk_url, received) =
+        stub_codes("/robot/send", Shape::ErrCode, vec![660026; 3]).await?;
+    let client = start_service(&dingtalk_url).await?;
+
+    // the caller may try again later, after the sender used up its attempts
+    let response = client
+        .execute(&request(WATERBOT_ID as i32, "1:1:202409021040"))
+        .await?;
+    assert_eq!(ExecuteStatus::Failed, response.status);
+    assert!(response.retryable);
+    assert_eq!(3, received.lock().unwrap().len());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_execute_unknown_event() -> anyhow::Result<()> {
+    let (dingtalk_url, received) = stub_dingtalk().await?;
+    let client = start_service(&dingtalk_url).await?;
+
+    let response = client.execute(&request(404, "1:1:202409021040")).await?;
+    assert_eq!(ExecuteStatus::NotFound, response.status);
+    assert!(response.error.is_some());
+    assert!(received.lock().unwrap().is_empty());
+    Ok(())
+}
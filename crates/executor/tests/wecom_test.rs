@@ -0,0 +1,78 @@
+mod common;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use common::{stub_webhook, Received, Shape};
+use executor::{Image, Mention, Message, Sender, WeComSender};
+use md5::{Digest, Md5};
+use serde_json::{json, Value};
+
+// WeCom robot stand-in answering the requests with the given error codes in
+// turn, 0 once they run out, and checking the md5 of images like WeCom does
+async fn stub_wecom(errcodes: Vec<i64>) -> anyhow::Result<(String, Received)> {
+    stub_webhook("/cgi-bin/webhook/send?key=key", move |request| {
+        let body = &request.body;
+        let errcode = match errcodes.get(request.index) {
+            Some(errcode) => *errcode,
+            None if body["msgtype"] == "image" && !verify_image(&body["image"]) => 40009,
+            None => 0,
+        };
+        Shape::ErrCode.reply(errcode, &format!("error {}", errcode))
+    })
+    .await
+}
+
+fn verify_image(image: &Value) -> bool {
+    let Some(bytes) = image["base64"]
+        .as_str()
+        .and_then(|base64| STANDARD.decode(base64).ok())
+    else {
+        return false;
+    };
+    image["md5"] == format!("{:x}", Md5::digest(bytes))
+}
+
+#[tokio::test]
+async fn test_send() -> anyhow::Result<()> {
+    let (url, received) = stub_wecom(vec![]).await?;
+    let message = Message::text("you are on duty")
+        .with_mention(Mention::User("wangqing".into()))
+        .with_mention(Mention::Mobile("13800000000".into()))
+        .with_image(Image::Bytes(b"\x89PNG".to_vec()));
+    let receipt = WeComSender::new(&url).send(&message).await?;
+    assert_eq!(
+        ("wecom", Some(0)),
+        (receipt.platform.as_str(), receipt.code)
+    );
+
+    let received = received.lock().unwrap();
+    assert_eq!(2, received.len());
+    assert_eq!(
+        json!({
+            "msgtype": "text",
+            "text": {
+                "content": "you are on duty",
+                "mentioned_list": ["wangqing"],
+                "mentioned_mobile_list": ["13800000000"],
+            },
+        }),
+        received[0]
+    );
+    assert_eq!("image", received[1]["msgtype"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_image_rejected() -> anyhow::Result<()> {
+    let (url, received) = stub_wecom(vec![0, 40009]).await?;
+    let message = Message::text("you are on duty").with_image(Image::Bytes(b"\x89PNG".to_vec()));
+
+    // the content went out, the send succeeds without repeating it
+    let receipt = WeComSender::new(&url).send(&message).await?;
+    assert_eq!(Some(0), receipt.code);
+    assert_eq!(
+        Some("error 0; 1 of 1 images not sent: wecom error 40009: error 40009"),
+        receipt.message.as_deref()
+    );
+    assert_eq!(2, received.lock().unwrap().len());
+    Ok(())
+}
@@ -1 +1,287 @@
+use std::{future::Future, sync::Arc};
 
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing, Json, Router,
+};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+
+use crate::{Task, TaskRepo};
+
+type ApiError = (StatusCode, String);
+
+/// Body of `POST /v1/tasks` and `PUT /v1/tasks/{id}`, times are local.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskSpec {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// 1-12, every month when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub months: Vec<i32>,
+    /// 1-31, every day when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<i32>,
+    /// 1-7 from Monday, every weekday when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weekdays: Vec<i32>,
+    /// `[hour, minute]` to execute at once a day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timepoint: Option<(i32, i32)>,
+    /// Minutes between executions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_gap: Option<i32>,
+    /// `[[hour, minute], [hour, minute]]` of the day to execute within.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<((i32, i32), (i32, i32))>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expect_times: Option<i32>,
+}
+
+impl TaskSpec {
+    /// The task the spec describes, the setters would silently drop values
+    /// out of range so they are rejected here.
+    pub fn task(&self) -> anyhow::Result<Task> {
+        let mut task = Task::new(&self.name);
+        task.set_description(&self.description);
+        if let Some(event_id) = self.event_id {
+            task.set_event_id(event_id);
+        }
+        if let Some(template) = &self.template {
+            task.set_template(template);
+        }
+        for &month in &self.months {
+            check_range("month", month, 1, 12)?;
+            task.set_month(month);
+        }
+        for &day in &self.days {
+            check_range("day", day, 1, 31)?;
+            task.set_day(day);
+        }
+        for &weekday in &self.weekdays {
+            check_range("weekday", weekday, 1, 7)?;
+            task.set_weekday(weekday);
+        }
+        if let Some((hour, minute)) = self.timepoint {
+            check_range("hour", hour, 0, 23)?;
+            check_range("minute", minute, 0, 59)?;
+            task.set_timepoint(hour, minute);
+        }
+        if let Some(time_gap) = self.time_gap {
+            check_range("time gap", time_gap, 1, 1440)?;
+            task.set_time_gap(time_gap);
+        }
+        if let Some((start, end)) = self.duration {
+            for (hour, minute) in [start, end] {
+                check_range("hour", hour, 0, 23)?;
+                check_range("minute", minute, 0, 59)?;
+            }
+            if start > end {
+                anyhow::bail!("duration ends before it starts");
+            }
+            task.set_duration(start, end);
+        }
+        if let Some(expect_times) = self.expect_times {
+            check_range("expect times", expect_times, 1, i32::MAX)?;
+            task.set_expect_times(expect_times);
+        }
+        Ok(task)
+    }
+}
+
+fn check_range(name: &str, value: i32, min: i32, max: i32) -> anyhow::Result<()> {
+    if value < min || value > max {
+        anyhow::bail!("{} {} is not within {}-{}", name, value, min, max);
+    }
+    Ok(())
+}
+
+/// A task as answered by the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskView {
+    pub id: i32,
+    #[serde(flatten)]
+    pub spec: TaskSpec,
+    pub execute_times: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_executed_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl From<&Task> for TaskView {
+    fn from(task: &Task) -> TaskView {
+        TaskView {
+            id: task.id(),
+            spec: TaskSpec {
+                name: task.name().into(),
+                description: task.description().into(),
+                event_id: task.event_id(),
+                template: task.template().map(Into::into),
+                months: task.month().unwrap_or_default(),
+                days: task.day().unwrap_or_default(),
+                weekdays: task.weekday().unwrap_or_default(),
+                timepoint: task.timepoint(),
+                time_gap: task.time_gap(),
+                duration: task.duration(),
+                expect_times: task.expect_times(),
+            },
+            execute_times: task.execute_times(),
+            last_executed_at: task.last_executed_at(),
+            last_status: task.last_status().map(Into::into),
+            last_error: task.last_error().map(Into::into),
+        }
+    }
+}
+
+/// Routes creating, reading, updating and deleting tasks. Writes go through
+/// the repo, so running schedulers pick them up without a full reload.
+pub fn router(repo: Arc<TaskRepo>) -> Router {
+    Router::new()
+        .route("/health", routing::get(|| async { "ok" }))
+        .route("/v1/tasks", routing::get(list_tasks).post(create_task))
+        .route(
+            "/v1/tasks/:id",
+            routing::get(get_task).put(update_task).delete(delete_task),
+        )
+        .with_state(repo)
+}
+
+/// Serve the task API until `shutdown` resolves.
+pub async fn serve<F>(listener: TcpListener, repo: Arc<TaskRepo>, shutdown: F) -> anyhow::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    axum::serve(listener, router(repo))
+        .with_graceful_shutdown(shutdown)
+        .await?;
+    Ok(())
+}
+
+async fn list_tasks(State(repo): State<Arc<TaskRepo>>) -> Result<Json<Vec<TaskView>>, ApiError> {
+    let tasks = repo.list_tasks().await.map_err(internal)?;
+    Ok(Json(tasks.iter().map(TaskView::from).collect()))
+}
+
+async fn get_task(
+    State(repo): State<Arc<TaskRepo>>,
+    Path(id): Path<i32>,
+) -> Result<Json<TaskView>, ApiError> {
+    let task = find_task(&repo, id).await?;
+    Ok(Json(TaskView::from(&task)))
+}
+
+async fn create_task(
+    State(repo): State<Arc<TaskRepo>>,
+    Json(spec): Json<TaskSpec>,
+) -> Result<(StatusCode, Json<TaskView>), ApiError> {
+    let task = spec.task().map_err(bad_request)?;
+    let id = repo.create_task(&task).await.map_err(internal)?;
+    println!("Created task {}: {}", id, task.name());
+    let task = find_task(&repo, id).await?;
+    Ok((StatusCode::CREATED, Json(TaskView::from(&task))))
+}
+
+async fn update_task(
+    State(repo): State<Arc<TaskRepo>>,
+    Path(id): Path<i32>,
+    Json(spec): Json<TaskSpec>,
+) -> Result<Json<TaskView>, ApiError> {
+    let definition = spec.task().map_err(bad_request)?;
+    if !repo
+        .redefine_task(id, &definition)
+        .await
+        .map_err(internal)?
+    {
+        return Err(not_found(id));
+    }
+    println!("Updated task {}", id);
+    let task = find_task(&repo, id).await?;
+    Ok(Json(TaskView::from(&task)))
+}
+
+async fn delete_task(
+    State(repo): State<Arc<TaskRepo>>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, ApiError> {
+    find_task(&repo, id).await?;
+    repo.delete_task(id).await.map_err(internal)?;
+    println!("Deleted task {}", id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_task(repo: &TaskRepo, id: i32) -> Result<Task, ApiError> {
+    repo.find_task_by_id(id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(id))
+}
+
+fn not_found(id: i32) -> ApiError {
+    (StatusCode::NOT_FOUND, format!("no task {}", id))
+}
+
+fn bad_request(err: anyhow::Error) -> ApiError {
+    (StatusCode::BAD_REQUEST, format!("{:#}", err))
+}
+
+fn internal(err: anyhow::Error) -> ApiError {
+    eprintln!("Task API failed: {:#}", err);
+    (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_task_spec() -> anyhow::Result<()> {
+        let spec: TaskSpec = serde_json::from_value(json!({
+            "name": "drink water",
+            "event_id": 1,
+            "weekdays": [1, 2, 3, 4, 5],
+            "time_gap": 40,
+            "duration": [[9, 0], [18, 30]],
+        }))?;
+        let task = spec.task()?;
+        assert_eq!(Some(vec![1, 2, 3, 4, 5]), task.weekday());
+        assert_eq!(Some(((9, 0), (18, 30))), task.duration());
+        assert_eq!(None, task.month());
+
+        let view = TaskView::from(&task);
+        assert_eq!(
+            json!({
+                "id": 0,
+                "name": "drink water",
+                "description": "",
+                "event_id": 1,
+                "weekdays": [1, 2, 3, 4, 5],
+                "time_gap": 40,
+                "duration": [[9, 0], [18, 30]],
+                "execute_times": 0,
+            }),
+            serde_json::to_value(&view)?
+        );
+
+        let spec = TaskSpec {
+            months: vec![0],
+            ..spec
+        };
+        assert_eq!(
+            "month 0 is not within 1-12",
+            spec.task().unwrap_err().to_string()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+mod entity;
+mod repo;
+
+pub use entity::{ExecutionFilter, ExecutionPage, ExecutionRecord};
+pub use repo::ExecutionRepo;
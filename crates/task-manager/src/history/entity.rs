@@ -0,0 +1,247 @@
+use chrono::{DateTime, Local};
+use executor::ExecutionOutcome;
+use sqlx::FromRow;
+
+use crate::TaskDue;
+
+/// One attempt at executing a task occurrence.
+#[derive(Debug, Clone)]
+pub struct ExecutionRecord {
+    id: i64,
+    task_id: i32,
+    run_id: Option<i64>,
+    occurrence_key: String,
+    sequence: i32,
+    scheduled_at: DateTime<Local>,
+    started_at: DateTime<Local>,
+    finished_at: DateTime<Local>,
+    worker_id: Option<String>,
+    event_id: Option<i32>,
+    outcome: ExecutionOutcome,
+    error: Option<String>,
+}
+
+impl ExecutionRecord {
+    pub fn new(
+        due: &TaskDue,
+        started_at: DateTime<Local>,
+        result: &anyhow::Result<Option<ExecutionOutcome>>,
+    ) -> ExecutionRecord {
+        let (outcome, error) = match result {
+            Ok(outcome) => (outcome.clone().unwrap_or_default(), None),
+            Err(err) => (
+                ExecutionOutcome::of_error(err).cloned().unwrap_or_default(),
+                Some(format!("{:#}", err)),
+            ),
+        };
+        ExecutionRecord {
+            id: 0,
+            task_id: due.task().id(),
+            run_id: None,
+            occurrence_key: due.key().into(),
+            sequence: due.sequence(),
+            scheduled_at: due.scheduled_at(),
+            started_at,
+            finished_at: Local::now(),
+            worker_id: None,
+            event_id: due.task().event_id(),
+            outcome,
+            error,
+        }
+    }
+    pub fn set_run_id(&mut self, run_id: i64) -> &mut Self {
+        self.run_id = Some(run_id);
+        self
+    }
+    pub fn set_worker_id(&mut self, worker_id: &str) -> &mut Self {
+        self.worker_id = Some(worker_id.into());
+        self
+    }
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+    pub fn task_id(&self) -> i32 {
+        self.task_id
+    }
+    pub fn run_id(&self) -> Option<i64> {
+        self.run_id
+    }
+    pub fn occurrence_key(&self) -> &str {
+        &self.occurrence_key
+    }
+    pub fn sequence(&self) -> i32 {
+        self.sequence
+    }
+    pub fn scheduled_at(&self) -> DateTime<Local> {
+        self.scheduled_at
+    }
+    pub fn started_at(&self) -> DateTime<Local> {
+        self.started_at
+    }
+    pub fn finished_at(&self) -> DateTime<Local> {
+        self.finished_at
+    }
+    pub fn worker_id(&self) -> Option<&str> {
+        self.worker_id.as_deref()
+    }
+    pub fn event_id(&self) -> Option<i32> {
+        self.event_id
+    }
+    /// Executor, sender, rendered content and platform response.
+    pub fn outcome(&self) -> &ExecutionOutcome {
+        &self.outcome
+    }
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Narrows an execution history query, unset fields match everything.
+#[derive(Debug, Clone)]
+pub struct ExecutionFilter {
+    pub task_id: Option<i32>,
+    pub occurrence_key: Option<String>,
+    pub succeeded: Option<bool>,
+    /// Inclusive lower bound of the scheduled time.
+    pub scheduled_from: Option<DateTime<Local>>,
+    /// Exclusive upper bound of the scheduled time.
+    pub scheduled_to: Option<DateTime<Local>>,
+    /// 1-based page number.
+    pub page: u32,
+    pub page_size: u32,
+}
+
+impl Default for ExecutionFilter {
+    fn default() -> Self {
+        ExecutionFilter {
+            task_id: None,
+            occurrence_key: None,
+            succeeded: None,
+            scheduled_from: None,
+            scheduled_to: None,
+            page: 1,
+            page_size: 20,
+        }
+    }
+}
+
+impl ExecutionFilter {
+    pub(crate) fn limit(&self) -> u32 {
+        self.page_size.clamp(1, 500)
+    }
+
+    pub(crate) fn offset(&self) -> u64 {
+        (self.page.max(1) as u64 - 1) * self.limit() as u64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExecutionPage {
+    pub records: Vec<ExecutionRecord>,
+    /// Records matching the filter across all pages.
+    pub total: i64,
+}
+
+#[derive(Debug, FromRow)]
+pub struct ExecutionRecordDAO {
+    pub id: i64,
+    pub task_id: i64,
+    pub run_id: Option<i64>,
+    pub occurrence_key: String,
+    pub sequence: i32,
+    pub scheduled_at: DateTime<Local>,
+    pub started_at: DateTime<Local>,
+    pub finished_at: DateTime<Local>,
+    pub worker_id: Option<String>,
+    pub event_id: Option<i32>,
+    pub executor: Option<String>,
+    pub sender: Option<String>,
+    pub content: Option<String>,
+    pub response_code: Option<i64>,
+    pub response_message: Option<String>,
+    pub succeeded: bool,
+    pub error: Option<String>,
+}
+
+impl From<ExecutionRecordDAO> for ExecutionRecord {
+    fn from(value: ExecutionRecordDAO) -> Self {
+        ExecutionRecord {
+            id: value.id,
+            task_id: value.task_id as i32,
+            run_id: value.run_id,
+            occurrence_key: value.occurrence_key,
+            sequence: value.sequence,
+            scheduled_at: value.scheduled_at,
+            started_at: value.started_at,
+            finished_at: value.finished_at,
+            worker_id: value.worker_id,
+            event_id: value.event_id,
+            outcome: ExecutionOutcome {
+                executor: value.executor.unwrap_or_default(),
+                sender: value.sender.unwrap_or_default(),
+                content: value.content.unwrap_or_default(),
+                response_code: value.response_code,
+                response_message: value.response_message,
+            },
+            error: match (value.succeeded, value.error) {
+                (false, None) => Some(String::new()),
+                (_, error) => error,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use executor::WebhookError;
+
+    use crate::Task;
+
+    use super::*;
+
+    #[test]
+    fn test_execution_record() {
+        let mut task = Task::new("demo");
+        task.set_event_id(0);
+        let due = TaskDue::new(task, &Local::now());
+        let started_at = Local::now();
+
+        let record = ExecutionRecord::new(&due, started_at, &Ok(None));
+        assert!(record.succeeded());
+        assert_eq!(due.key(), record.occurrence_key());
+        assert_eq!(Some(0), record.event_id());
+
+        let record = ExecutionRecord::new(&due, started_at, &Err(anyhow::anyhow!("timeout")));
+        assert!(!record.succeeded());
+        assert_eq!(Some("timeout"), record.error());
+
+        // what the platform replied when it rejected the content
+        let err = WebhookError::new("dingtalk", 310000, "keywords not in content").into();
+        let outcome = ExecutionOutcome::undelivered("waterbot", "dingtalk", "drink water", &err);
+        let record = ExecutionRecord::new(&due, started_at, &Err(outcome.attach_to(err)));
+        assert!(!record.succeeded());
+        assert_eq!("drink water", record.outcome().content);
+        assert_eq!(Some(310000), record.outcome().response_code);
+    }
+
+    #[test]
+    fn test_execution_filter_page() {
+        let filter = ExecutionFilter::default();
+        assert_eq!((20, 0), (filter.limit(), filter.offset()));
+        let filter = ExecutionFilter {
+            page: 3,
+            page_size: 50,
+            ..Default::default()
+        };
+        assert_eq!((50, 100), (filter.limit(), filter.offset()));
+        let filter = ExecutionFilter {
+            page: 0,
+            page_size: 0,
+            ..Default::default()
+        };
+        assert_eq!((1, 0), (filter.limit(), filter.offset()));
+    }
+}
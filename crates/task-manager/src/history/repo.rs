@@ -0,0 +1,125 @@
+use sqlx::{MySql, MySqlPool, QueryBuilder};
+
+use super::entity::{ExecutionFilter, ExecutionPage, ExecutionRecord, ExecutionRecordDAO};
+
+pub struct ExecutionRepo {
+    pool: MySqlPool,
+}
+
+impl ExecutionRepo {
+    pub fn new(pool: MySqlPool) -> ExecutionRepo {
+        ExecutionRepo { pool }
+    }
+
+    pub async fn create_record(&self, record: &ExecutionRecord) -> anyhow::Result<i64> {
+        let outcome = record.outcome();
+        let result = sqlx::query(
+            r#"
+INSERT INTO `task_execution` (
+    `task_id`,
+    `run_id`,
+    `occurrence_key`,
+    `sequence`,
+    `scheduled_at`,
+    `started_at`,
+    `finished_at`,
+    `worker_id`,
+    `event_id`,
+    `executor`,
+    `sender`,
+    `content`,
+    `response_code`,
+    `response_message`,
+    `succeeded`,
+    `error`
+) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+        )
+        .bind(record.task_id())
+        .bind(record.run_id())
+        .bind(record.occurrence_key())
+        .bind(record.sequence())
+        .bind(record.scheduled_at())
+        .bind(record.started_at())
+        .bind(record.finished_at())
+        .bind(record.worker_id())
+        .bind(record.event_id())
+        .bind(&outcome.executor)
+        .bind(&outcome.sender)
+        .bind(&outcome.content)
+        .bind(outcome.response_code)
+        .bind(&outcome.response_message)
+        .bind(record.succeeded())
+        .bind(record.error())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_id() as i64)
+    }
+
+    /// Records matching the filter, latest scheduled first.
+    pub async fn list_records(&self, filter: &ExecutionFilter) -> anyhow::Result<ExecutionPage> {
+        let mut count = QueryBuilder::<MySql>::new("SELECT COUNT(*) FROM `task_execution`");
+        push_filter(&mut count, filter);
+        let total = count
+            .build_query_scalar::<i64>()
+            .fetch_one(&self.pool)
+            .await?;
+
+        let mut query = QueryBuilder::<MySql>::new(
+            r#"
+SELECT
+    `id`,
+    `task_id`,
+    `run_id`,
+    `occurrence_key`,
+    `sequence`,
+    `scheduled_at`,
+    `started_at`,
+    `finished_at`,
+    `worker_id`,
+    `event_id`,
+    `executor`,
+    `sender`,
+    `content`,
+    `response_code`,
+    `response_message`,
+    `succeeded`,
+    `error`
+FROM `task_execution`"#,
+        );
+        push_filter(&mut query, filter);
+        query
+            .push(" ORDER BY `scheduled_at` DESC, `id` DESC LIMIT ")
+            .push_bind(filter.limit())
+            .push(" OFFSET ")
+            .push_bind(filter.offset());
+        let records = query
+            .build_query_as::<ExecutionRecordDAO>()
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|dao| dao.into())
+            .collect();
+        Ok(ExecutionPage { records, total })
+    }
+}
+
+fn push_filter(query: &mut QueryBuilder<MySql>, filter: &ExecutionFilter) {
+    query.push(" WHERE 1 = 1");
+    if let Some(task_id) = filter.task_id {
+        query.push(" AND `task_id` = ").push_bind(task_id);
+    }
+    if let Some(occurrence_key) = &filter.occurrence_key {
+        query
+            .push(" AND `occurrence_key` = ")
+            .push_bind(occurrence_key.clone());
+    }
+    if let Some(succeeded) = filter.succeeded {
+        query.push(" AND `succeeded` = ").push_bind(succeeded);
+    }
+    if let Some(from) = filter.scheduled_from {
+        query.push(" AND `scheduled_at` >= ").push_bind(from);
+    }
+    if let Some(to) = filter.scheduled_to {
+        query.push(" AND `scheduled_at` < ").push_bind(to);
+    }
+}
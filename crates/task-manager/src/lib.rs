@@ -1,6 +1,14 @@
 mod creator;
+mod history;
+mod run;
 mod scheduler;
 mod task;
 
-pub use scheduler::Scheduler;
-pub use task::{Task, TaskRepo};
+pub use creator::{router, serve, TaskSpec, TaskView};
+pub use history::{ExecutionFilter, ExecutionPage, ExecutionRecord, ExecutionRepo};
+pub use run::{RunEnqueuer, RunRepo, RunState, RunWorker, TaskRun};
+pub use scheduler::{
+    shutdown_signal, ExecutorDispatcher, HashRing, MemberRepo, RemoteDispatcher, Runner, Scheduler,
+    SchedulerBuilder, TaskDue, TaskHandler,
+};
+pub use task::{Task, TaskChange, TaskRepo, TaskStore};
@@ -0,0 +1,7 @@
+mod entity;
+mod repo;
+mod worker;
+
+pub use entity::{RunState, TaskRun};
+pub use repo::RunRepo;
+pub use worker::{RunEnqueuer, RunWorker};
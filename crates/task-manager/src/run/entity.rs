@@ -0,0 +1,180 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Local};
+use sqlx::FromRow;
+
+use crate::{Task, TaskDue};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunState {
+    /// Waiting for a worker.
+    Pending,
+    /// Claimed by a worker holding an unexpired lease.
+    Running,
+    Succeeded,
+    /// The last attempt failed, claimable again once its backoff elapsed.
+    Failed,
+    /// Out of attempts or failed permanently, left for inspection and replay.
+    Dead,
+}
+
+impl RunState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunState::Pending => "pending",
+            RunState::Running => "running",
+            RunState::Succeeded => "succeeded",
+            RunState::Failed => "failed",
+            RunState::Dead => "dead",
+        }
+    }
+}
+
+impl fmt::Display for RunState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RunState {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(RunState::Pending),
+            "running" => Ok(RunState::Running),
+            "succeeded" => Ok(RunState::Succeeded),
+            "failed" => Ok(RunState::Failed),
+            "dead" => Ok(RunState::Dead),
+            state => anyhow::bail!("unknown run state: {}", state),
+        }
+    }
+}
+
+/// A queued execution of a due task occurrence.
+#[derive(Debug, Clone)]
+pub struct TaskRun {
+    id: i64,
+    task_id: i32,
+    event_id: Option<i32>,
+    occurrence_key: String,
+    sequence: i32,
+    scheduled_at: DateTime<Local>,
+    dispatched_at: DateTime<Local>,
+    state: RunState,
+    attempts: i32,
+    available_at: DateTime<Local>,
+    worker_id: Option<String>,
+    lease_expires_at: Option<DateTime<Local>>,
+    last_error: Option<String>,
+}
+
+impl TaskRun {
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+    pub fn task_id(&self) -> i32 {
+        self.task_id
+    }
+    pub fn event_id(&self) -> Option<i32> {
+        self.event_id
+    }
+    pub fn occurrence_key(&self) -> &str {
+        &self.occurrence_key
+    }
+    pub fn sequence(&self) -> i32 {
+        self.sequence
+    }
+    pub fn scheduled_at(&self) -> DateTime<Local> {
+        self.scheduled_at
+    }
+    pub fn state(&self) -> RunState {
+        self.state
+    }
+    pub fn attempts(&self) -> i32 {
+        self.attempts
+    }
+    /// When the run may be claimed again.
+    pub fn available_at(&self) -> DateTime<Local> {
+        self.available_at
+    }
+    pub fn worker_id(&self) -> Option<&str> {
+        self.worker_id.as_deref()
+    }
+    pub fn lease_expires_at(&self) -> Option<DateTime<Local>> {
+        self.lease_expires_at
+    }
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// The occurrence this run executes, with the current task snapshot.
+    pub fn due(&self, task: Task) -> TaskDue {
+        TaskDue::restore(
+            task,
+            self.scheduled_at,
+            self.dispatched_at,
+            self.sequence,
+            &self.occurrence_key,
+        )
+        .with_attempt(self.attempts.max(1) as u32)
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct TaskRunDAO {
+    pub id: i64,
+    pub task_id: i64,
+    pub event_id: Option<i32>,
+    pub occurrence_key: String,
+    pub sequence: i32,
+    pub scheduled_at: DateTime<Local>,
+    pub dispatched_at: DateTime<Local>,
+    pub state: String,
+    pub attempts: i32,
+    pub available_at: DateTime<Local>,
+    pub worker_id: Option<String>,
+    pub lease_expires_at: Option<DateTime<Local>>,
+    pub last_error: Option<String>,
+}
+
+impl TryFrom<TaskRunDAO> for TaskRun {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TaskRunDAO) -> Result<Self, Self::Error> {
+        Ok(TaskRun {
+            id: value.id,
+            task_id: value.task_id.try_into()?,
+            event_id: value.event_id,
+            occurrence_key: value.occurrence_key,
+            sequence: value.sequence,
+            scheduled_at: value.scheduled_at,
+            dispatched_at: value.dispatched_at,
+            state: value.state.parse()?,
+            attempts: value.attempts,
+            available_at: value.available_at,
+            worker_id: value.worker_id,
+            lease_expires_at: value.lease_expires_at,
+            last_error: value.last_error,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_state() {
+        for state in [
+            RunState::Pending,
+            RunState::Running,
+            RunState::Succeeded,
+            RunState::Failed,
+            RunState::Dead,
+        ] {
+            assert_eq!(state, state.as_str().parse().unwrap());
+        }
+        assert!("done".parse::<RunState>().is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::Local;
+use executor::{ExecutionOutcome, RetryPolicy};
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+use crate::{ExecutionRecord, ExecutionRepo, TaskDue, TaskHandler, TaskStore};
+
+use super::{entity::TaskRun, repo::RunRepo};
+
+const DEFAULT_LEASE: Duration = Duration::from_secs(60);
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Scheduler handler queueing due occurrences as runs for the workers.
+pub struct RunEnqueuer {
+    runs: Arc<RunRepo>,
+}
+
+impl RunEnqueuer {
+    pub fn new(runs: Arc<RunRepo>) -> RunEnqueuer {
+        RunEnqueuer { runs }
+    }
+}
+
+#[async_trait]
+impl TaskHandler for RunEnqueuer {
+    async fn handle(&self, due: &TaskDue) -> anyhow::Result<Option<ExecutionOutcome>> {
+        if !self.runs.enqueue(due).await? {
+            println!("Skip {}: already queued", due);
+        }
+        Ok(None)
+    }
+
+    fn executes(&self) -> bool {
+        false
+    }
+}
+
+pub(super) fn default_retry() -> RetryPolicy {
+    RetryPolicy::new(3).with_backoff(Duration::from_secs(30), Duration::from_secs(600))
+}
+
+/// Claims queued runs and executes them with a [`TaskHandler`].
+pub struct RunWorker {
+    worker_id: String,
+    runs: Arc<RunRepo>,
+    store: Arc<dyn TaskStore>,
+    handler: Arc<dyn TaskHandler>,
+    history: Option<Arc<ExecutionRepo>>,
+    lease: Duration,
+    poll_interval: Duration,
+    retry: RetryPolicy,
+    shutdown: CancellationToken,
+    drain_timeout: Duration,
+}
+
+impl RunWorker {
+    pub fn new<H: TaskHandler + 'static>(
+        worker_id: &str,
+        runs: Arc<RunRepo>,
+        store: Arc<dyn TaskStore>,
+        handler: H,
+    ) -> RunWorker {
+        RunWorker {
+            worker_id: worker_id.into(),
+            runs,
+            store,
+            handler: Arc::new(handler),
+            history: None,
+            lease: DEFAULT_LEASE,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            retry: default_retry(),
+            shutdown: CancellationToken::new(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        }
+    }
+
+    /// Record every attempt in the execution history.
+    pub fn with_history(mut self, history: Arc<ExecutionRepo>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// How long a claimed run is held without a heartbeat, 60 seconds by
+    /// default. Heartbeats are sent every third of it.
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease.max(Duration::from_secs(3));
+        self
+    }
+
+    /// How long to wait when the queue is empty, 1 second by default.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Attempts and backoff of failed runs before they are dead, by default
+    /// 3 attempts backing off from 30 seconds for the errors
+    /// [`executor::is_retryable`] accepts. Expired leases count as attempts.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// The worker stops claiming once the token is cancelled and returns after
+    /// finishing the run at hand.
+    pub fn with_shutdown(mut self, token: CancellationToken) -> Self {
+        self.shutdown = token;
+        self
+    }
+
+    /// How long the run at hand may take to finish after shutdown, 30 seconds
+    /// by default. A run still going afterwards is abandoned and claimed again
+    /// once its lease expired.
+    pub fn with_drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+
+    pub async fn run(self) -> anyhow::Result<()> {
+        while !self.shutdown.is_cancelled() {
+            let stepped = tokio::select! {
+                stepped = self.step() => stepped,
+                _ = async {
+                    self.shutdown.cancelled().await;
+                    time::sleep(self.drain_timeout).await;
+                } => {
+                    eprintln!(
+                        "Worker {} abandoned its run after {:?}, its lease will expire",
+                        self.worker_id, self.drain_timeout
+                    );
+                    break;
+                }
+            };
+            match stepped {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(err) => eprintln!("Worker {} failed: {:#}", self.worker_id, err),
+            }
+            tokio::select! {
+                _ = time::sleep(self.poll_interval) => {}
+                _ = self.shutdown.cancelled() => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Execute one run, `false` when there was nothing to claim.
+    async fn step(&self) -> anyhow::Result<bool> {
+        let expired = self.runs.requeue_expired(self.retry.max_attempts()).await?;
+        if expired > 0 {
+            println!("Released {} runs with expired leases", expired);
+        }
+        let Some(run) = self.runs.claim(&self.worker_id, self.lease).await? else {
+            return Ok(false);
+        };
+        let outcome = self.execute(&run).await;
+        if let Err(err) = &outcome {
+            eprintln!("Run {} failed: {:#}", run.id(), err);
+        }
+        let completed = self
+            .runs
+            .complete(&run, outcome.as_ref().map(|_| ()), &self.retry)
+            .await?;
+        match completed {
+            Some(state) => println!("Run {} of task {}: {}", run.id(), run.task_id(), state),
+            None => eprintln!(
+                "Run {} of task {}: lease lost, left to the next claim",
+                run.id(),
+                run.task_id()
+            ),
+        }
+        Ok(true)
+    }
+
+    async fn execute(&self, run: &TaskRun) -> anyhow::Result<Option<ExecutionOutcome>> {
+        let task = self
+            .store
+            .find_task_by_id(run.task_id())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("task {} not found", run.task_id()))?;
+        let due = run.due(task);
+        let started_at = Local::now();
+        let outcome = tokio::select! {
+            outcome = self.handler.handle(&due) => outcome,
+            lost = self.keep_lease(run) => lost.map(|_| None),
+        };
+        if let Some(history) = &self.history {
+            let mut record = ExecutionRecord::new(&due, started_at, &outcome);
+            record.set_run_id(run.id()).set_worker_id(&self.worker_id);
+            if let Err(err) = history.create_record(&record).await {
+                eprintln!("Failed to record history of {}: {:#}", due, err);
+            }
+        }
+        if let Err(err) = self.store.record(&due, outcome.as_ref().map(|_| ())).await {
+            eprintln!("Failed to record {}: {:#}", due, err);
+        }
+        outcome
+    }
+
+    // resolves only when the lease is lost
+    async fn keep_lease(&self, run: &TaskRun) -> anyhow::Result<()> {
+        let mut interval = time::interval(self.lease / 3);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            match self.runs.heartbeat(run, self.lease).await {
+                Ok(true) => {}
+                Ok(false) => anyhow::bail!("lease of run {} lost", run.id()),
+                Err(err) => eprintln!("Failed to heartbeat run {}: {:#}", run.id(), err),
+            }
+        }
+    }
+}
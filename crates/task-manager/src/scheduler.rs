@@ -1,3 +1,15 @@
+mod dispatcher;
+mod event;
+mod handler;
+mod runner;
+#[allow(clippy::module_inception)]
 mod scheduler;
+mod shard;
 
+pub use dispatcher::{ExecutorDispatcher, RemoteDispatcher};
+pub use event::TaskDue;
+pub use executor::shutdown_signal;
+pub use handler::TaskHandler;
+pub use runner::{Runner, SchedulerBuilder};
 pub use scheduler::Scheduler;
+pub use shard::{HashRing, MemberRepo};
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use executor::{
+    ExecuteRequest, ExecutionContext, ExecutionOutcome, ExecutorClient, ExecutorManager,
+    RemoteError,
+};
+use serde_json::json;
+
+use super::{event::TaskDue, handler::TaskHandler};
+
+/// Runs the executor a task is bound to through its `event_id`.
+pub struct ExecutorDispatcher {
+    manager: Arc<ExecutorManager>,
+}
+
+impl ExecutorDispatcher {
+    pub fn new(manager: Arc<ExecutorManager>) -> ExecutorDispatcher {
+        ExecutorDispatcher { manager }
+    }
+}
+
+#[async_trait]
+impl TaskHandler for ExecutorDispatcher {
+    async fn handle(&self, due: &TaskDue) -> anyhow::Result<Option<ExecutionOutcome>> {
+        let event_id = due
+            .task()
+            .event_id()
+            .ok_or_else(|| anyhow::anyhow!("task {} has no event id", due.task().id()))?;
+        if !self.manager.contains(event_id) {
+            anyhow::bail!("no executor registered for event id {}", event_id);
+        }
+        let ctx = ExecutionContext::new(due.task().id(), due.scheduled_at())
+            .with_attempt(due.attempt())
+            .with_payload(payload(due));
+        let outcome = self.manager.execute(event_id, &ctx).await?;
+        println!("Executed {} with executor {}", due, event_id);
+        Ok(Some(outcome))
+    }
+}
+
+/// Sends due tasks to a remote executor service.
+pub struct RemoteDispatcher {
+    client: ExecutorClient,
+}
+
+impl RemoteDispatcher {
+    pub fn new(base_url: &str) -> RemoteDispatcher {
+        RemoteDispatcher {
+            client: ExecutorClient::new(base_url),
+        }
+    }
+}
+
+#[async_trait]
+impl TaskHandler for RemoteDispatcher {
+    async fn handle(&self, due: &TaskDue) -> anyhow::Result<Option<ExecutionOutcome>> {
+        let task = due.task();
+        let event_id = task
+            .event_id()
+            .ok_or_else(|| anyhow::anyhow!("task {} has no event id", task.id()))?;
+        let response = self
+            .client
+            .execute(&ExecuteRequest {
+                task_id: task.id(),
+                event_id,
+                occurrence_key: due.key().into(),
+                scheduled_at: Some(due.scheduled_at()),
+                attempt: due.attempt(),
+                payload: payload(due),
+            })
+            .await?;
+        if !response.is_succeeded() {
+            let message = format!(
+                "executor replied {:?}: {}",
+                response.status,
+                response.error.unwrap_or_default()
+            );
+            let err = RemoteError::new(&message).with_retryable(response.retryable);
+            let err = anyhow::Error::new(err);
+            return Err(match response.outcome {
+                Some(outcome) => outcome.attach_to(err),
+                None => err,
+            });
+        }
+        println!("Executed {} remotely with event id {}", due, event_id);
+        Ok(response.outcome)
+    }
+}
+
+fn payload(due: &TaskDue) -> serde_json::Value {
+    json!({
+        "name": due.task().name(),
+        "description": due.task().description(),
+        "sequence": due.sequence(),
+        "template": due.task().template(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::{routing, Json, Router};
+    use chrono::Local;
+    use executor::{new_executor_manager, serve, WATERBOT_ID};
+    use tokio::net::TcpListener;
+
+    use crate::Task;
+
+    use super::*;
+
+    // DingTalk robot stand-in rejecting the first message for a missing
+    // keyword and taking the others; returns the url and the requests received
+    async fn stub_dingtalk() -> anyhow::Result<(String, Arc<AtomicUsize>)> {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let received = requests.clone();
+        let app = Router::new().route(
+            "/robot/send",
+            routing::post(move || async move {
+                match received.fetch_add(1, Ordering::SeqCst) {
+                    0 => Json(json!({ "errcode": 310000, "errmsg": "keywords not in content" })),
+                    _ => Json(json!({ "errcode": 0, "errmsg": "ok" })),
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/robot/send", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Ok((url, requests))
+    }
+
+    #[tokio::test]
+    async fn test_remote_dispatcher() -> anyhow::Result<()> {
+        let (dingtalk_url, requests) = stub_dingtalk().await?;
+        let manager = Arc::new(new_executor_manager(&dingtalk_url));
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        tokio::spawn(serve(listener, manager, std::future::pending()));
+
+        let dispatcher = RemoteDispatcher::new(&url);
+        let mut task = Task::new("demo");
+        task.set_event_id(WATERBOT_ID as i32);
+        let due = TaskDue::new(task, &Local::now());
+
+        let err = dispatcher.handle(&due).await.unwrap_err();
+        assert!(!executor::is_retryable(&err));
+        let outcome = ExecutionOutcome::of_error(&err).unwrap();
+        assert_eq!(Some(310000), outcome.response_code);
+        assert!(outcome.content.contains("第1轮"));
+
+        // the retry of a failed occurrence executes again
+        let outcome = dispatcher
+            .handle(&due.clone().with_attempt(2))
+            .await?
+            .unwrap();
+        assert_eq!(Some(0), outcome.response_code);
+        assert_eq!(2, requests.load(Ordering::SeqCst));
+
+        // a delivered occurrence is not sent twice
+        let outcome = dispatcher
+            .handle(&due.clone().with_attempt(3))
+            .await?
+            .unwrap();
+        assert_eq!(Some(0), outcome.response_code);
+        assert_eq!(2, requests.load(Ordering::SeqCst));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_event_id() {
+        let dispatcher = ExecutorDispatcher::new(Arc::new(new_executor_manager("")));
+        let mut task = Task::new("demo");
+        let due = TaskDue::new(task.clone(), &Local::now());
+        assert!(dispatcher.handle(&due).await.is_err());
+
+        task.set_event_id(404);
+        let due = TaskDue::new(task, &Local::now());
+        let err = dispatcher.handle(&due).await.unwrap_err();
+        assert_eq!("no executor registered for event id 404", err.to_string());
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+
+use chrono::{DateTime, Local};
+
+use crate::Task;
+
+/// An occurrence of a task that is due, emitted by the `Scheduler`.
+#[derive(Debug, Clone)]
+pub struct TaskDue {
+    task: Task,
+    scheduled_at: DateTime<Local>,
+    dispatched_at: DateTime<Local>,
+    sequence: i32,
+    attempt: u32,
+    key: String,
+}
+
+impl TaskDue {
+    pub fn new(task: Task, now: &DateTime<Local>) -> TaskDue {
+        let scheduled_at = task.scheduled_at(now);
+        let sequence = task.execute_times() + 1;
+        // a fallback scheduled time moves with `now`, so it is left out of the
+        // key for a redispatch in a later minute to be seen as the same
+        let key = match task.planned_at(now) {
+            Some(planned_at) => format!(
+                "{}:{}:{}",
+                task.id(),
+                sequence,
+                planned_at.format("%Y%m%d%H%M")
+            ),
+            None => format!("{}:{}", task.id(), sequence),
+        };
+        TaskDue {
+            task,
+            scheduled_at,
+            dispatched_at: *now,
+            sequence,
+            attempt: 1,
+            key,
+        }
+    }
+    /// Rebuild an occurrence dispatched before, e.g. from a queued run.
+    pub(crate) fn restore(
+        task: Task,
+        scheduled_at: DateTime<Local>,
+        dispatched_at: DateTime<Local>,
+        sequence: i32,
+        key: &str,
+    ) -> TaskDue {
+        TaskDue {
+            task,
+            scheduled_at,
+            dispatched_at,
+            sequence,
+            attempt: 1,
+            key: key.into(),
+        }
+    }
+    pub(crate) fn with_attempt(mut self, attempt: u32) -> Self {
+        self.attempt = attempt.max(1);
+        self
+    }
+    /// Snapshot of the task when it was found due.
+    pub fn task(&self) -> &Task {
+        &self.task
+    }
+    pub fn scheduled_at(&self) -> DateTime<Local> {
+        self.scheduled_at
+    }
+    pub fn dispatched_at(&self) -> DateTime<Local> {
+        self.dispatched_at
+    }
+    /// 1-based number of this occurrence among all executions of the task.
+    pub fn sequence(&self) -> i32 {
+        self.sequence
+    }
+    /// 1-based attempt at executing the occurrence, above 1 when retried.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+    /// Identifies the occurrence, stays the same when it is dispatched twice.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl fmt::Display for TaskDue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "task {} run #{} scheduled for {}",
+            self.task.id(),
+            self.sequence,
+            self.scheduled_at.format("%Y-%m-%d %H:%M")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_task_due() {
+        let now = Local.with_ymd_and_hms(2024, 9, 2, 10, 40, 12).unwrap();
+        let mut task = Task::new("demo");
+        task.set_timepoint(10, 40).execute().execute();
+        let due = TaskDue::new(task, &now);
+        assert_eq!(3, due.sequence());
+        assert_eq!(now, due.dispatched_at());
+        assert_eq!(
+            Local.with_ymd_and_hms(2024, 9, 2, 10, 40, 0).unwrap(),
+            due.scheduled_at()
+        );
+        assert_eq!("0:3:202409021040", due.key());
+        assert_eq!(
+            "task 0 run #3 scheduled for 2024-09-02 10:40",
+            due.to_string()
+        );
+
+        let mut task = Task::new("demo");
+        task.set_time_gap(40);
+        let due = TaskDue::new(task.clone(), &now);
+        let later = TaskDue::new(task, &(now + chrono::TimeDelta::minutes(1)));
+        assert_eq!("0:1", due.key());
+        assert_eq!(due.key(), later.key());
+    }
+}
@@ -0,0 +1,33 @@
+use std::future::Future;
+
+use async_trait::async_trait;
+use executor::ExecutionOutcome;
+
+use super::event::TaskDue;
+
+/// Handles the occurrences the scheduler claimed. Handlers running an
+/// executor return what it delivered for the execution history.
+#[async_trait]
+pub trait TaskHandler: Send + Sync {
+    async fn handle(&self, due: &TaskDue) -> anyhow::Result<Option<ExecutionOutcome>>;
+
+    /// Whether a successful `handle` executed the occurrence. Handlers only
+    /// queueing it are called before the occurrence is claimed, so a queue
+    /// keyed on the occurrence never misses a claimed one, and leave its
+    /// status and history to whoever executes it.
+    fn executes(&self) -> bool {
+        true
+    }
+}
+
+#[async_trait]
+impl<F, Fut> TaskHandler for F
+where
+    F: Fn(TaskDue) -> Fut + Send + Sync,
+    Fut: Future<Output = anyhow::Result<()>> + Send,
+{
+    async fn handle(&self, due: &TaskDue) -> anyhow::Result<Option<ExecutionOutcome>> {
+        self(due.clone()).await?;
+        Ok(None)
+    }
+}
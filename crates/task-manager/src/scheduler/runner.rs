@@ -0,0 +1,415 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::Local;
+use tokio::{sync::Semaphore, task::JoinSet, time};
+use tokio_util::sync::CancellationToken;
+
+use crate::{ExecutionRecord, ExecutionRepo, TaskStore};
+
+use super::{event::TaskDue, handler::TaskHandler, scheduler::Scheduler, shard::MemberRepo};
+
+const DEFAULT_CONCURRENCY: usize = 8;
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Builds a [`Runner`] around a [`Scheduler`].
+pub struct SchedulerBuilder {
+    scheduler: Scheduler,
+    store: Arc<dyn TaskStore>,
+    handler: Option<Arc<dyn TaskHandler>>,
+    history: Option<Arc<ExecutionRepo>>,
+    concurrency: usize,
+    shutdown: CancellationToken,
+    drain_timeout: Duration,
+}
+
+impl SchedulerBuilder {
+    pub fn new(store: Arc<dyn TaskStore>) -> SchedulerBuilder {
+        SchedulerBuilder {
+            scheduler: Scheduler::new(store.clone()),
+            store,
+            handler: None,
+            history: None,
+            concurrency: DEFAULT_CONCURRENCY,
+            shutdown: CancellationToken::new(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        }
+    }
+
+    /// Called for every claimed occurrence, either a [`TaskHandler`] or an
+    /// async closure taking a [`TaskDue`].
+    pub fn handler<H: TaskHandler + 'static>(mut self, handler: H) -> Self {
+        self.handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Record every handled occurrence in the execution history.
+    pub fn history(mut self, history: Arc<ExecutionRepo>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// How many occurrences are handled at the same time, 8 by default.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// The runner stops once the token is cancelled.
+    pub fn shutdown(mut self, token: CancellationToken) -> Self {
+        self.shutdown = token;
+        self
+    }
+
+    /// How long in-flight occurrences may take to finish after shutdown,
+    /// 30 seconds by default. Those still running afterwards are aborted and
+    /// recorded as failed.
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.scheduler = self.scheduler.with_interval(interval);
+        self
+    }
+
+    pub fn reconcile_interval(mut self, interval: Duration) -> Self {
+        self.scheduler = self.scheduler.with_reconcile_interval(interval);
+        self
+    }
+
+    pub fn shard(mut self, members: Arc<MemberRepo>, instance_id: &str) -> Self {
+        self.scheduler = self.scheduler.with_shard(members, instance_id);
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<Runner> {
+        let handler = self
+            .handler
+            .ok_or_else(|| anyhow::anyhow!("scheduler handler is not set"))?;
+        let scheduler = self.scheduler.with_capacity(self.concurrency);
+        Ok(Runner {
+            scheduler,
+            store: self.store,
+            handler,
+            history: self.history,
+            concurrency: self.concurrency,
+            shutdown: self.shutdown,
+            drain_timeout: self.drain_timeout,
+        })
+    }
+}
+
+/// Runs the due → claim → handle → record cycle until shut down.
+pub struct Runner {
+    scheduler: Scheduler,
+    store: Arc<dyn TaskStore>,
+    handler: Arc<dyn TaskHandler>,
+    history: Option<Arc<ExecutionRepo>>,
+    concurrency: usize,
+    shutdown: CancellationToken,
+    drain_timeout: Duration,
+}
+
+// claimed occurrences which are not recorded yet, by key
+type InFlight = Arc<Mutex<HashMap<String, TaskDue>>>;
+
+impl Runner {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let mut rx = self.scheduler.run()?;
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+        let in_flight = InFlight::default();
+        let mut running = JoinSet::new();
+        loop {
+            let due = tokio::select! {
+                due = rx.recv() => match due {
+                    Some(due) => due,
+                    None => break,
+                },
+                _ = self.shutdown.cancelled() => break,
+            };
+            let permit = tokio::select! {
+                permit = permits.clone().acquire_owned() => permit?,
+                _ = self.shutdown.cancelled() => break,
+            };
+            while running.try_join_next().is_some() {}
+            let store = self.store.clone();
+            let handler = self.handler.clone();
+            let history = self.history.clone();
+            let in_flight = in_flight.clone();
+            running.spawn(async move {
+                let processed = process(
+                    store.as_ref(),
+                    handler.as_ref(),
+                    history.as_deref(),
+                    &in_flight,
+                    &due,
+                )
+                .await;
+                if let Err(err) = processed {
+                    eprintln!("Failed to process {}: {:#}", due, err);
+                }
+                drop(permit);
+            });
+        }
+
+        // stop polling, due occurrences left in the channel are not claimed
+        // yet and will be found again by the next scheduler
+        drop(rx);
+        let drained = time::timeout(self.drain_timeout, async {
+            while running.join_next().await.is_some() {}
+        })
+        .await;
+        if drained.is_err() {
+            running.shutdown().await;
+            let interrupted = in_flight
+                .lock()
+                .unwrap()
+                .drain()
+                .map(|(_, due)| due)
+                .collect::<Vec<_>>();
+            for due in interrupted {
+                eprintln!("Interrupted {} on shutdown", due);
+                let err = anyhow::anyhow!("interrupted by shutdown");
+                if let Err(err) = self.store.record(&due, Err(&err)).await {
+                    eprintln!("Failed to record {}: {:#}", due, err);
+                }
+            }
+        }
+        self.scheduler.leave().await
+    }
+}
+
+async fn process(
+    store: &dyn TaskStore,
+    handler: &dyn TaskHandler,
+    history: Option<&ExecutionRepo>,
+    in_flight: &InFlight,
+    due: &TaskDue,
+) -> anyhow::Result<()> {
+    if !handler.executes() {
+        // a failed queueing leaves the occurrence unclaimed for the next tick
+        handler.handle(due).await?;
+        if !store.claim(due).await? {
+            println!("Skip {}: already claimed", due);
+        }
+        return Ok(());
+    }
+    if !store.claim(due).await? {
+        println!("Skip {}: already claimed", due);
+        return Ok(());
+    }
+    in_flight
+        .lock()
+        .unwrap()
+        .insert(due.key().into(), due.clone());
+    let started_at = Local::now();
+    let outcome = handler.handle(due).await;
+    if let Err(err) = &outcome {
+        eprintln!("Failed to handle {}: {:#}", due, err);
+    }
+    if let Some(history) = history {
+        let record = ExecutionRecord::new(due, started_at, &outcome);
+        if let Err(err) = history.create_record(&record).await {
+            eprintln!("Failed to record history of {}: {:#}", due, err);
+        }
+    }
+    let recorded = store.record(due, outcome.as_ref().map(|_| ())).await;
+    in_flight.lock().unwrap().remove(due.key());
+    recorded
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    use async_trait::async_trait;
+    use executor::ExecutionOutcome;
+
+    use crate::Task;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        tasks: Mutex<Vec<Task>>,
+        outcomes: Mutex<Vec<bool>>,
+    }
+
+    #[async_trait]
+    impl TaskStore for MemoryStore {
+        async fn list_tasks(&self) -> anyhow::Result<Vec<Task>> {
+            Ok(self.tasks.lock().unwrap().clone())
+        }
+
+        async fn find_task_by_id(&self, id: i32) -> anyhow::Result<Option<Task>> {
+            let tasks = self.tasks.lock().unwrap();
+            Ok(tasks.iter().find(|task| task.id() == id).cloned())
+        }
+
+        async fn claim(&self, due: &TaskDue) -> anyhow::Result<bool> {
+            let mut tasks = self.tasks.lock().unwrap();
+            match tasks.iter_mut().find(|task| task.id() == due.task().id()) {
+                Some(task) if task.execute_times() == due.sequence() - 1 => {
+                    task.execute();
+                    Ok(true)
+                }
+                _ => Ok(false),
+            }
+        }
+
+        async fn record(
+            &self,
+            _: &TaskDue,
+            outcome: Result<(), &anyhow::Error>,
+        ) -> anyhow::Result<()> {
+            self.outcomes.lock().unwrap().push(outcome.is_ok());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runner_cycle() -> anyhow::Result<()> {
+        let mut task = Task::new("demo");
+        task.set_event_id(1).set_time_gap(60);
+        let store = Arc::new(MemoryStore::default());
+        store.tasks.lock().unwrap().push(task);
+
+        let handled = Arc::new(AtomicUsize::new(0));
+        let counter = handled.clone();
+        let shutdown = CancellationToken::new();
+        let runner = SchedulerBuilder::new(store.clone())
+            .handler(move |due: TaskDue| {
+                let counter = counter.clone();
+                async move {
+                    assert_eq!(1, due.sequence());
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            })
+            .interval(Duration::from_millis(10))
+            .concurrency(2)
+            .shutdown(shutdown.clone())
+            .build()?;
+        let running = tokio::spawn(runner.run());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown.cancel();
+        running.await??;
+
+        assert_eq!(1, handled.load(Ordering::SeqCst));
+        assert_eq!(vec![true], *store.outcomes.lock().unwrap());
+        assert_eq!(1, store.tasks.lock().unwrap()[0].execute_times());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_runner_drain_timeout() -> anyhow::Result<()> {
+        let mut task = Task::new("demo");
+        task.set_event_id(1).set_time_gap(60);
+        let store = Arc::new(MemoryStore::default());
+        store.tasks.lock().unwrap().push(task);
+
+        let shutdown = CancellationToken::new();
+        let runner = SchedulerBuilder::new(store.clone())
+            .handler(|_: TaskDue| async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(())
+            })
+            .interval(Duration::from_millis(10))
+            .shutdown(shutdown.clone())
+            .drain_timeout(Duration::from_millis(50))
+            .build()?;
+        let running = tokio::spawn(runner.run());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(1), running).await???;
+
+        assert_eq!(vec![false], *store.outcomes.lock().unwrap());
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct Queue {
+        queued: AtomicUsize,
+        failing: bool,
+    }
+
+    #[async_trait]
+    impl TaskHandler for Queue {
+        async fn handle(&self, _: &TaskDue) -> anyhow::Result<Option<ExecutionOutcome>> {
+            if self.failing {
+                anyhow::bail!("queue unavailable");
+            }
+            self.queued.fetch_add(1, Ordering::SeqCst);
+            Ok(None)
+        }
+
+        fn executes(&self) -> bool {
+            false
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runner_queueing_handler() -> anyhow::Result<()> {
+        let mut task = Task::new("demo");
+        task.set_event_id(1).set_time_gap(60);
+        let store = Arc::new(MemoryStore::default());
+        store.tasks.lock().unwrap().push(task);
+
+        let shutdown = CancellationToken::new();
+        let runner = SchedulerBuilder::new(store.clone())
+            .handler(Queue::default())
+            .interval(Duration::from_millis(10))
+            .shutdown(shutdown.clone())
+            .build()?;
+        let running = tokio::spawn(runner.run());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown.cancel();
+        running.await??;
+
+        // the status is left to whoever executes the queued occurrence
+        assert!(store.outcomes.lock().unwrap().is_empty());
+        assert_eq!(1, store.tasks.lock().unwrap()[0].execute_times());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_runner_queueing_failed() -> anyhow::Result<()> {
+        let mut task = Task::new("demo");
+        task.set_event_id(1).set_time_gap(60);
+        let store = Arc::new(MemoryStore::default());
+        store.tasks.lock().unwrap().push(task);
+
+        let shutdown = CancellationToken::new();
+        let runner = SchedulerBuilder::new(store.clone())
+            .handler(Queue {
+                failing: true,
+                ..Default::default()
+            })
+            .interval(Duration::from_millis(10))
+            .shutdown(shutdown.clone())
+            .build()?;
+        let running = tokio::spawn(runner.run());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown.cancel();
+        running.await??;
+
+        // not queued, so not claimed either and due again on the next tick
+        assert!(store.outcomes.lock().unwrap().is_empty());
+        assert_eq!(0, store.tasks.lock().unwrap()[0].execute_times());
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_requires_handler() {
+        let store = Arc::new(MemoryStore::default());
+        assert!(SchedulerBuilder::new(store).build().is_err());
+    }
+}
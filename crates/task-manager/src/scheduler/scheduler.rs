@@ -1,42 +1,287 @@
-use std::{
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use chrono::Local;
+use tokio::{
     sync::{
-        mpsc::{self, Receiver},
-        Arc,
+        broadcast::{self, error::RecvError},
+        mpsc::{self, Receiver, Sender},
     },
-    thread,
-    time::Duration,
+    time,
+};
+
+use crate::{Task, TaskChange, TaskStore};
+
+use super::{
+    event::TaskDue,
+    shard::{HashRing, MemberRepo},
 };
 
-use crate::TaskRepo;
+// members missing heartbeats for this long are dropped from the ring
+const MEMBER_TTL: Duration = Duration::from_secs(30);
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+const DEFAULT_CAPACITY: usize = 64;
+const DEFAULT_RECONCILE_INTERVAL: Duration = Duration::from_secs(300);
 
 pub struct Scheduler {
-    repo: Arc<TaskRepo>,
+    repo: Arc<dyn TaskStore>,
+    shard: Option<Shard>,
+    interval: Duration,
+    reconcile_interval: Duration,
+    capacity: usize,
+}
+
+#[derive(Clone)]
+struct Shard {
+    instance_id: String,
+    members: Arc<MemberRepo>,
 }
 
 impl Scheduler {
-    pub fn new(repo: Arc<TaskRepo>) -> Scheduler {
-        Scheduler { repo }
+    pub fn new(repo: Arc<dyn TaskStore>) -> Scheduler {
+        Scheduler {
+            repo,
+            shard: None,
+            interval: DEFAULT_INTERVAL,
+            reconcile_interval: DEFAULT_RECONCILE_INTERVAL,
+            capacity: DEFAULT_CAPACITY,
+        }
+    }
+
+    /// Only dispatch the tasks this instance owns on the consistent-hash ring
+    /// built from the alive members.
+    pub fn with_shard(mut self, members: Arc<MemberRepo>, instance_id: &str) -> Self {
+        self.shard = Some(Shard {
+            instance_id: instance_id.into(),
+            members,
+        });
+        self
+    }
+
+    /// How often the tasks in memory are checked, 10 seconds by default.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// How often all tasks are reloaded to catch edits made around the store,
+    /// e.g. directly in SQL, 5 minutes by default. Stores without change
+    /// notifications are reloaded on every tick instead.
+    pub fn with_reconcile_interval(mut self, interval: Duration) -> Self {
+        self.reconcile_interval = interval;
+        self
+    }
+
+    /// How many due tasks may wait for the consumer before the scheduler
+    /// stops polling, 64 by default.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
     }
 
-    pub fn run(&self) -> anyhow::Result<Receiver<i32>> {
-        let (tx, rx) = mpsc::channel::<i32>();
+    /// Start checking tasks in the background. The scheduler stops once the
+    /// returned receiver is dropped.
+    pub fn run(&self) -> anyhow::Result<Receiver<TaskDue>> {
+        let (tx, rx) = mpsc::channel::<TaskDue>(self.capacity);
         let repo = self.repo.clone();
+        let shard = self.shard.clone();
+        let mut changes = repo.subscribe();
+        let mut interval = time::interval(self.interval);
+        interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+        let mut reconcile = time::interval(self.reconcile_interval);
+        reconcile.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
         tokio::spawn(async move {
+            let mut plan = Plan {
+                tasks: HashMap::new(),
+                stale: true,
+            };
             loop {
-                if let Ok(tasks) = repo.list_tasks().await {
-                    let ready_task_id = tasks
-                        .into_iter()
-                        .filter(|task| task.ready_to_execute())
-                        .map(|task| task.id())
-                        .collect::<Vec<_>>();
-                    println!("Ready task id: {:?}", ready_task_id);
-                    for id in ready_task_id {
-                        tx.send(id).unwrap();
+                tokio::select! {
+                    _ = interval.tick() => {
+                        if changes.is_none() || plan.stale {
+                            plan.reload(repo.as_ref()).await;
+                        }
+                        if let Err(err) = dispatch(&plan, shard.as_ref(), &tx).await {
+                            eprintln!("Failed to dispatch tasks: {}", err);
+                        }
+                    }
+                    _ = reconcile.tick(), if changes.is_some() => {
+                        plan.reload(repo.as_ref()).await;
                     }
+                    change = recv_change(&mut changes) => match change {
+                        Ok(change) => plan.apply(repo.as_ref(), change).await,
+                        // missed some changes, start over from the table
+                        Err(RecvError::Lagged(_)) => plan.stale = true,
+                        Err(RecvError::Closed) => changes = None,
+                    },
+                    _ = tx.closed() => break,
                 }
-                thread::sleep(Duration::from_secs(10));
             }
+            println!("Scheduler stopped: receiver dropped");
         });
         Ok(rx)
     }
+
+    /// Leave the shard ring so the remaining instances take the tasks over
+    /// right away instead of after the member TTL.
+    pub async fn leave(&self) -> anyhow::Result<()> {
+        if let Some(shard) = &self.shard {
+            shard.members.leave(&shard.instance_id).await?;
+        }
+        Ok(())
+    }
+}
+
+/// The tasks the scheduler knows about, kept up to date by change
+/// notifications and periodic reloads.
+struct Plan {
+    tasks: HashMap<i32, Task>,
+    stale: bool,
+}
+
+impl Plan {
+    async fn reload(&mut self, repo: &dyn TaskStore) {
+        match repo.list_tasks().await {
+            Ok(tasks) => {
+                self.tasks = tasks.into_iter().map(|task| (task.id(), task)).collect();
+                self.stale = false;
+            }
+            Err(err) => {
+                eprintln!("Failed to reload tasks: {}", err);
+                self.stale = true;
+            }
+        }
+    }
+
+    async fn apply(&mut self, repo: &dyn TaskStore, change: TaskChange) {
+        let id = change.id();
+        if let TaskChange::Deleted(_) = change {
+            self.tasks.remove(&id);
+            return;
+        }
+        match repo.find_task_by_id(id).await {
+            Ok(Some(task)) => {
+                self.tasks.insert(id, task);
+            }
+            Ok(None) => {
+                self.tasks.remove(&id);
+            }
+            Err(err) => {
+                eprintln!("Failed to reload task {}: {}", id, err);
+                self.stale = true;
+            }
+        }
+    }
+}
+
+async fn recv_change(
+    changes: &mut Option<broadcast::Receiver<TaskChange>>,
+) -> Result<TaskChange, RecvError> {
+    match changes {
+        Some(changes) => changes.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn dispatch(plan: &Plan, shard: Option<&Shard>, tx: &Sender<TaskDue>) -> anyhow::Result<()> {
+    // without a membership view we can't tell which tasks are ours, so a failed
+    // refresh skips the round instead of double dispatching
+    let owner = match shard {
+        Some(shard) => Some((&shard.instance_id, current_ring(shard).await?)),
+        None => None,
+    };
+    let ready_tasks = plan
+        .tasks
+        .values()
+        .filter(|task| match &owner {
+            Some((instance_id, ring)) => ring.owns(instance_id, task.id()),
+            None => true,
+        })
+        .filter(|task| task.ready_to_execute())
+        .cloned()
+        .collect::<Vec<_>>();
+    println!(
+        "Ready task id: {:?}",
+        ready_tasks.iter().map(|task| task.id()).collect::<Vec<_>>()
+    );
+    for task in ready_tasks {
+        // waits while the channel is full, the consumer sets the pace
+        tx.send(TaskDue::new(task, &Local::now())).await?;
+    }
+    Ok(())
+}
+
+async fn current_ring(shard: &Shard) -> anyhow::Result<HashRing> {
+    shard.members.heartbeat(&shard.instance_id).await?;
+    Ok(HashRing::new(
+        shard.members.alive_members(MEMBER_TTL).await?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+
+    use super::*;
+
+    struct WatchedStore {
+        tasks: Mutex<Vec<Task>>,
+        changes: broadcast::Sender<TaskChange>,
+    }
+
+    #[async_trait]
+    impl TaskStore for WatchedStore {
+        async fn list_tasks(&self) -> anyhow::Result<Vec<Task>> {
+            Ok(self.tasks.lock().unwrap().clone())
+        }
+
+        async fn find_task_by_id(&self, id: i32) -> anyhow::Result<Option<Task>> {
+            let tasks = self.tasks.lock().unwrap();
+            Ok(tasks.iter().find(|task| task.id() == id).cloned())
+        }
+
+        async fn claim(&self, _: &TaskDue) -> anyhow::Result<bool> {
+            Ok(true)
+        }
+
+        async fn record(&self, _: &TaskDue, _: Result<(), &anyhow::Error>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn subscribe(&self) -> Option<broadcast::Receiver<TaskChange>> {
+            Some(self.changes.subscribe())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hot_reload() -> anyhow::Result<()> {
+        let store = Arc::new(WatchedStore {
+            tasks: Mutex::new(vec![]),
+            changes: broadcast::channel(8).0,
+        });
+        let scheduler = Scheduler::new(store.clone())
+            .with_interval(Duration::from_millis(10))
+            .with_reconcile_interval(Duration::from_secs(3600));
+        let mut rx = scheduler.run()?;
+        time::sleep(Duration::from_millis(50)).await;
+
+        // written around the store, only the reconciliation would see it
+        let mut task = Task::new("demo");
+        task.set_event_id(1).set_time_gap(60);
+        store.tasks.lock().unwrap().push(task);
+        time::sleep(Duration::from_millis(50)).await;
+        assert!(rx.try_recv().is_err());
+
+        store.changes.send(TaskChange::Created(0))?;
+        let due = time::timeout(Duration::from_secs(1), rx.recv()).await?;
+        assert_eq!(Some(0), due.map(|due| due.task().id()));
+
+        store.changes.send(TaskChange::Deleted(0))?;
+        time::sleep(Duration::from_millis(50)).await;
+        while rx.try_recv().is_ok() {}
+        time::sleep(Duration::from_millis(50)).await;
+        assert!(rx.try_recv().is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,141 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use sqlx::MySqlPool;
+
+// virtual nodes placed on the ring for every member, smooths the distribution
+const VIRTUAL_NODES: usize = 64;
+
+/// Consistent-hash ring mapping task ids onto scheduler instances.
+#[derive(Debug, Default, Clone)]
+pub struct HashRing {
+    nodes: BTreeMap<u64, String>,
+}
+
+impl HashRing {
+    pub fn new<I, S>(members: I) -> HashRing
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut nodes = BTreeMap::new();
+        for member in members {
+            let member: String = member.into();
+            for replica in 0..VIRTUAL_NODES {
+                let point = hash(format!("{}#{}", member, replica).as_bytes());
+                nodes.insert(point, member.clone());
+            }
+        }
+        HashRing { nodes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The instance owning the given task, `None` when the ring has no member.
+    pub fn owner(&self, task_id: i32) -> Option<&str> {
+        let point = hash(&task_id.to_be_bytes());
+        self.nodes
+            .range(point..)
+            .next()
+            .or_else(|| self.nodes.iter().next())
+            .map(|(_, member)| member.as_str())
+    }
+
+    pub fn owns(&self, instance_id: &str, task_id: i32) -> bool {
+        self.owner(task_id) == Some(instance_id)
+    }
+}
+
+// FNV-1a with a splitmix64 finalizer, stable across processes and builds so
+// every instance agrees on the ring
+fn hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+    hash ^ (hash >> 31)
+}
+
+/// Scheduler instances registered in the `scheduler_member` table.
+pub struct MemberRepo {
+    pool: MySqlPool,
+}
+
+impl MemberRepo {
+    pub fn new(pool: MySqlPool) -> MemberRepo {
+        MemberRepo { pool }
+    }
+
+    /// Register the instance, or refresh its heartbeat when already registered.
+    pub async fn heartbeat(&self, instance_id: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+INSERT INTO `scheduler_member` (`instance_id`, `heartbeat_at`)
+VALUES (?, CURRENT_TIMESTAMP)
+ON DUPLICATE KEY UPDATE `heartbeat_at` = CURRENT_TIMESTAMP"#,
+        )
+        .bind(instance_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Instances whose heartbeat is not older than `ttl`.
+    pub async fn alive_members(&self, ttl: Duration) -> anyhow::Result<Vec<String>> {
+        Ok(sqlx::query_scalar::<_, String>(
+            r#"
+SELECT `instance_id`
+FROM `scheduler_member`
+WHERE `heartbeat_at` >= CURRENT_TIMESTAMP - INTERVAL ? SECOND
+ORDER BY `instance_id`"#,
+        )
+        .bind(ttl.as_secs())
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn leave(&self, instance_id: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM `scheduler_member` WHERE `instance_id` = ?")
+            .bind(instance_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_owner() {
+        let ring = HashRing::default();
+        assert!(ring.is_empty());
+        assert_eq!(None, ring.owner(1));
+
+        let ring = HashRing::new(["a"]);
+        assert!((0..100).all(|id| ring.owns("a", id)));
+
+        let ring = HashRing::new(["a", "b", "c"]);
+        let owned = |member: &str| (0..3000).filter(|id| ring.owns(member, *id)).count();
+        assert_eq!(3000, owned("a") + owned("b") + owned("c"));
+        for member in ["a", "b", "c"] {
+            assert!(owned(member) > 500);
+        }
+    }
+
+    #[test]
+    fn test_ring_rebalance() {
+        let before = HashRing::new(["a", "b", "c"]);
+        let after = HashRing::new(["a", "b", "c", "d"]);
+        for id in 0..3000 {
+            let owner = after.owner(id).unwrap();
+            // a joining member only takes tasks over, it never shuffles the others
+            assert!(owner == "d" || Some(owner) == before.owner(id));
+        }
+    }
+}
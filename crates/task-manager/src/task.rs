@@ -1,5 +1,7 @@
 mod entity;
 mod repo;
+mod store;
 
 pub use entity::Task;
 pub use repo::TaskRepo;
+pub use store::{TaskChange, TaskStore};
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Datelike, Local, Timelike};
+use chrono::{DateTime, Datelike, Local, TimeDelta, Timelike};
 use sqlx::FromRow;
 
 const ONE_DAY_MINUTE: i32 = 1440;
@@ -24,6 +24,11 @@ pub struct Task {
     execute_times: i32,
     last_executed_at: Option<DateTime<Local>>,
     event_id: Option<i32>,
+    // message template handed to the executor
+    template: Option<String>,
+    // outcome of the last handled occurrence
+    last_status: Option<String>,
+    last_error: Option<String>,
 }
 
 impl Task {
@@ -51,12 +56,9 @@ impl Task {
         &self.description
     }
     pub fn set_month(&mut self, month: i32) -> &mut Self {
-        let mut m = match self.month {
-            Some(m) => m,
-            None => 0,
-        };
+        let mut m = self.month.unwrap_or_default();
         if month.le(&12) {
-            m = m | (1 << (month - 1));
+            m |= 1 << (month - 1);
             self.month = Some(m);
         }
         self
@@ -73,17 +75,14 @@ impl Task {
     }
     pub fn match_month(&self, month: i32) -> bool {
         match self.month {
-            Some(m) => (m | !(1 << month - 1)).eq(&!0),
+            Some(m) => (m | !(1 << (month - 1))).eq(&!0),
             None => true,
         }
     }
     pub fn set_weekday(&mut self, weekday: i32) -> &mut Self {
-        let mut w = match self.weekday {
-            Some(w) => w,
-            None => 0,
-        };
+        let mut w = self.weekday.unwrap_or_default();
         if weekday.le(&7) {
-            w = w | (1 << (weekday - 1));
+            w |= 1 << (weekday - 1);
             self.weekday = Some(w);
         }
         self
@@ -100,17 +99,14 @@ impl Task {
     }
     pub fn match_weekday(&self, weekday: i32) -> bool {
         match self.weekday {
-            Some(w) => (w | !(1 << weekday - 1)).eq(&!0),
+            Some(w) => (w | !(1 << (weekday - 1))).eq(&!0),
             None => true,
         }
     }
     pub fn set_day(&mut self, day: i32) -> &mut Self {
-        let mut d = match self.day {
-            Some(d) => d,
-            None => 0,
-        };
+        let mut d = self.day.unwrap_or_default();
         if day.le(&31) {
-            d = d | (1 << (day - 1));
+            d |= 1 << (day - 1);
             self.day = Some(d);
         }
         self
@@ -127,7 +123,7 @@ impl Task {
     }
     pub fn match_day(&self, day: i32) -> bool {
         match self.day {
-            Some(d) => (d | !(1 << day - 1)).eq(&!0),
+            Some(d) => (d | !(1 << (day - 1))).eq(&!0),
             None => true,
         }
     }
@@ -178,6 +174,10 @@ impl Task {
         self
     }
 
+    pub fn time_gap(&self) -> Option<i32> {
+        self.time_gap
+    }
+
     pub fn reach_gap(&self, now: &DateTime<Local>) -> bool {
         match self.time_gap {
             Some(gap) => {
@@ -252,6 +252,17 @@ impl Task {
         self
     }
 
+    /// Template the executor renders the content from, overriding the
+    /// executor's own one.
+    pub fn template(&self) -> Option<&str> {
+        self.template.as_deref()
+    }
+
+    pub fn set_template(&mut self, template: &str) -> &mut Self {
+        self.template = Some(template.into());
+        self
+    }
+
     pub fn ready_to_execute(&self) -> bool {
         // if not event id is bound, fail fast
         if self.event_id.is_none() {
@@ -271,18 +282,49 @@ impl Task {
             return false;
         }
 
-        if self.match_month(month)
+        self.match_month(month)
             && self.match_day(day)
             && self.match_weekday((weekday.num_days_from_monday() + 1).try_into().unwrap())
             && self.match_duration(hour, minute)
             && self.match_timepoint(&now)
             && self.less_expect_times()
             && self.reach_gap(&now)
-        {
-            true
-        } else {
-            false
-        }
+    }
+
+    /// The time the occurrence due at `now` was planned for, falls back to
+    /// `now` truncated to the minute when `planned_at` knows none.
+    pub fn scheduled_at(&self, now: &DateTime<Local>) -> DateTime<Local> {
+        self.planned_at(now).unwrap_or_else(|| {
+            now.with_second(0)
+                .and_then(|now| now.with_nanosecond(0))
+                .unwrap_or(*now)
+        })
+    }
+
+    /// Today's timepoint, or the moment the gap was reached (not earlier than
+    /// the duration start), if it is not after `now`. Unlike the fallback of
+    /// `scheduled_at` it only depends on the persisted definition and state.
+    pub fn planned_at(&self, now: &DateTime<Local>) -> Option<DateTime<Local>> {
+        let today = |minutes: i32| {
+            now.date_naive()
+                .and_hms_opt((minutes / 60) as u32, (minutes % 60) as u32, 0)
+                .and_then(|at| at.and_local_timezone(Local).single())
+        };
+        let scheduled = match self.timepoint {
+            Some(timepoint) => today(timepoint),
+            None => {
+                let gap_reached = self
+                    .time_gap
+                    .zip(self.last_executed_at)
+                    .map(|(gap, last)| last + TimeDelta::minutes(gap.into()));
+                let duration_start = self.duration.and_then(|(start, _)| today(start));
+                match (gap_reached, duration_start) {
+                    (Some(gap_reached), Some(start)) => Some(gap_reached.max(start)),
+                    (gap_reached, start) => gap_reached.or(start),
+                }
+            }
+        };
+        scheduled.filter(|at| at.le(now))
     }
 
     pub fn execute(&mut self) -> &mut Self {
@@ -296,7 +338,15 @@ impl Task {
     }
 
     pub fn last_executed_at(&self) -> Option<DateTime<Local>> {
-        self.last_executed_at.clone()
+        self.last_executed_at
+    }
+
+    pub fn last_status(&self) -> Option<&str> {
+        self.last_status.as_deref()
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
     }
 }
 
@@ -315,7 +365,10 @@ pub struct TaskDAO {
     pub duration_end: Option<i32>,
     pub execute_times: i32,
     pub event_id: Option<i32>,
+    pub template: Option<String>,
     pub last_executed_at: Option<DateTime<Local>>,
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
 }
 
 impl From<TaskDAO> for Task {
@@ -330,47 +383,37 @@ impl From<TaskDAO> for Task {
             weekday: value.weekday,
             timepoint: value.timepoint,
             time_gap: value.time_gap,
-            duration: if let Some(start) = value.duration_start {
-                if let Some(end) = value.duration_end {
-                    Some((start, end))
-                } else {
-                    None
-                }
-            } else {
-                None
-            },
+            duration: value.duration_start.zip(value.duration_end),
             event_id: value.event_id,
+            template: value.template,
             execute_times: value.execute_times,
             last_executed_at: value.last_executed_at,
+            last_status: value.last_status,
+            last_error: value.last_error,
         }
     }
 }
 
-impl Into<TaskDAO> for Task {
-    fn into(self) -> TaskDAO {
+impl From<Task> for TaskDAO {
+    fn from(value: Task) -> Self {
         TaskDAO {
-            id: self.id,
-            name: self.name.clone(),
-            description: self.description.clone(),
-            expect_times: self.expect_times,
-            month: self.month,
-            day: self.day,
-            weekday: self.weekday,
-            timepoint: self.timepoint,
-            time_gap: self.time_gap,
-            duration_start: if let Some(duration) = self.duration {
-                Some(duration.0)
-            } else {
-                None
-            },
-            duration_end: if let Some(duration) = self.duration {
-                Some(duration.1)
-            } else {
-                None
-            },
-            event_id: self.event_id,
-            execute_times: self.execute_times,
-            last_executed_at: self.last_executed_at,
+            id: value.id,
+            name: value.name,
+            description: value.description,
+            expect_times: value.expect_times,
+            month: value.month,
+            day: value.day,
+            weekday: value.weekday,
+            timepoint: value.timepoint,
+            time_gap: value.time_gap,
+            duration_start: value.duration.map(|duration| duration.0),
+            duration_end: value.duration.map(|duration| duration.1),
+            event_id: value.event_id,
+            template: value.template,
+            execute_times: value.execute_times,
+            last_executed_at: value.last_executed_at,
+            last_status: value.last_status,
+            last_error: value.last_error,
         }
     }
 }
@@ -379,6 +422,8 @@ impl Into<TaskDAO> for Task {
 mod tests {
     use std::{thread, time::Duration};
 
+    use chrono::TimeZone;
+
     use super::*;
 
     #[test]
@@ -424,6 +469,32 @@ mod tests {
         assert!(task.ready_to_execute());
     }
 
+    #[test]
+    fn test_scheduled_at() {
+        let now = Local.with_ymd_and_hms(2024, 9, 2, 10, 42, 17).unwrap();
+        let mut task = Task::new("demo");
+        assert_eq!(
+            Local.with_ymd_and_hms(2024, 9, 2, 10, 42, 0).unwrap(),
+            task.scheduled_at(&now)
+        );
+        assert_eq!(None, task.planned_at(&now));
+        task.set_duration((10, 0), (18, 0)).set_time_gap(40);
+        assert_eq!(
+            Local.with_ymd_and_hms(2024, 9, 2, 10, 0, 0).unwrap(),
+            task.scheduled_at(&now)
+        );
+        task.last_executed_at = Some(Local.with_ymd_and_hms(2024, 9, 2, 10, 0, 0).unwrap());
+        assert_eq!(
+            Local.with_ymd_and_hms(2024, 9, 2, 10, 40, 0).unwrap(),
+            task.scheduled_at(&now)
+        );
+        task.set_timepoint(8, 30);
+        assert_eq!(
+            Local.with_ymd_and_hms(2024, 9, 2, 8, 30, 0).unwrap(),
+            task.scheduled_at(&now)
+        );
+    }
+
     #[test]
     fn test_execute_task_timepoint() {
         let mut task = Task::new("demo");
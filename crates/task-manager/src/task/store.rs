@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use tokio::sync::broadcast;
+
+use crate::TaskDue;
+
+use super::entity::Task;
+
+/// A task row that was written through the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskChange {
+    Created(i32),
+    Updated(i32),
+    Deleted(i32),
+}
+
+impl TaskChange {
+    pub fn id(&self) -> i32 {
+        match self {
+            TaskChange::Created(id) | TaskChange::Updated(id) | TaskChange::Deleted(id) => *id,
+        }
+    }
+}
+
+/// Storage the scheduler reads tasks from and records executions to.
+#[async_trait]
+pub trait TaskStore: Send + Sync {
+    async fn list_tasks(&self) -> anyhow::Result<Vec<Task>>;
+
+    async fn find_task_by_id(&self, id: i32) -> anyhow::Result<Option<Task>>;
+
+    /// Mark the occurrence as executed. Returns `false` when it was already
+    /// claimed, e.g. by another scheduler instance.
+    async fn claim(&self, due: &TaskDue) -> anyhow::Result<bool>;
+
+    /// Save the outcome of handling a claimed occurrence.
+    async fn record(
+        &self,
+        due: &TaskDue,
+        outcome: Result<(), &anyhow::Error>,
+    ) -> anyhow::Result<()>;
+
+    /// Changes made through this store. Stores without notifications return
+    /// `None` and are fully reloaded on every scheduler tick.
+    fn subscribe(&self) -> Option<broadcast::Receiver<TaskChange>> {
+        None
+    }
+}
@@ -0,0 +1,83 @@
+use std::{
+    net::TcpListener as StdListener,
+    process::Stdio,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use axum::{routing, Json, Router};
+use chrono::Local;
+use executor::{ExecutionOutcome, ExecutorClient, WATERBOT_ID};
+use serde_json::json;
+use task_manager::{RemoteDispatcher, Task, TaskDue, TaskHandler};
+use tokio::{net::TcpListener, process::Command, time};
+
+// DingTalk robot stand-in rejecting the first message for a missing keyword
+// and taking the others; returns the url and the requests received
+async fn stub_dingtalk() -> anyhow::Result<(String, Arc<AtomicUsize>)> {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let received = requests.clone();
+    let app = Router::new().route(
+        "/robot/send",
+        routing::post(move || async move {
+            match received.fetch_add(1, Ordering::SeqCst) {
+                0 => Json(json!({ "errcode": 310000, "errmsg": "keywords not in content" })),
+                _ => Json(json!({ "errcode": 0, "errmsg": "ok" })),
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/robot/send", listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    Ok((url, requests))
+}
+
+// the executor binary and the task manager talk over HTTP as deployed
+#[tokio::test]
+async fn test_executor_process() -> anyhow::Result<()> {
+    let (dingtalk_url, requests) = stub_dingtalk().await?;
+    let addr = StdListener::bind("127.0.0.1:0")?.local_addr()?;
+    let mut child = Command::new(env!("CARGO_BIN_EXE_executor"))
+        .env("EXECUTOR_ADDR", addr.to_string())
+        .env("DINGTALK_URL", &dingtalk_url)
+        .env_remove("EXECUTOR_CONFIG")
+        .stdout(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let url = format!("http://{}", addr);
+    let client = ExecutorClient::new(&url);
+    let mut started = false;
+    for _ in 0..100 {
+        if client.health().await.is_ok() {
+            started = true;
+            break;
+        }
+        anyhow::ensure!(child.try_wait()?.is_none(), "executor exited");
+        time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(started, "executor did not start listening on {}", addr);
+
+    let dispatcher = RemoteDispatcher::new(&url);
+    let mut task = Task::new("demo");
+    task.set_event_id(WATERBOT_ID as i32);
+    let due = TaskDue::new(task, &Local::now());
+
+    let err = dispatcher.handle(&due).await.unwrap_err();
+    assert!(!executor::is_retryable(&err));
+    let outcome = ExecutionOutcome::of_error(&err).unwrap();
+    assert_eq!(Some(310000), outcome.response_code);
+
+    // the retry executes again, a repeat of the delivered occurrence does not
+    for _ in 0..2 {
+        let outcome = dispatcher.handle(&due).await?.unwrap();
+        assert_eq!(Some(0), outcome.response_code);
+    }
+    assert_eq!(2, requests.load(Ordering::SeqCst));
+
+    child.kill().await?;
+    Ok(())
+}